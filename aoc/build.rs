@@ -0,0 +1,126 @@
+// Generate the table of puzzle days from the day* directories in the workspace, so adding a day
+// only needs the directory and a dependency in Cargo.toml, which `aoc new` adds. Days of other years
+// than the default are in a directory named after the year, like 2022/day1_calorie_counting.
+// A day is run by its `pub fn solve`, or by the Solver type named in its Cargo.toml:
+//
+//     [package.metadata.aoc]
+//     solver = "Trebuchet"
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// the first number in the directory name is the puzzle number
+fn first_number(input: &str) -> Option<u32> {
+    let start_off = input.find(|c: char| c.is_ascii_digit())?;
+    let end_off = input[start_off..].find(|c: char| !c.is_ascii_digit()).unwrap_or(input[start_off..].len());
+    input[start_off..start_off+end_off].parse().ok()
+}
+
+// value of a `key = "value"` line in a section of a manifest, like "dependencies"
+fn manifest_value<'a>(manifest: &'a str, section: &str, key: &str) -> Option<&'a str> {
+    let mut in_section = false;
+    for line in manifest.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_section = name.trim() == section;
+        } else if let Some((k, v)) = line.split_once('=').filter(|_| in_section) {
+            if k.trim() == key {
+                return Some(v.trim().trim_matches('"'));
+            }
+        }
+    }
+    None
+}
+
+// Find the days in a directory: the root directory for the default year, or a directory named after the year.
+// Returns the table entries by puzzle number.
+fn scan_days(root: &Path, subdir: &str, year: u16, aoc_manifest: &str, errors: &mut Vec<String>) -> BTreeMap<u32, String> {
+    let mut days: BTreeMap<u32, String> = BTreeMap::new();
+    for entry in fs::read_dir(root.join(subdir)).unwrap() {
+        let path = entry.unwrap().path();
+        let dir = path.file_name().unwrap().to_string_lossy().to_string();
+        if !dir.starts_with("day") || !path.join("Cargo.toml").is_file() {
+            continue;
+        }
+        // the directory relative to the root, which is what the runner uses
+        let reldir = if subdir.is_empty() { dir.clone() } else { format!("{subdir}/{dir}") };
+        let Some(num) = first_number(&dir) else {
+            errors.push(format!("cannot find puzzle number in {reldir}"));
+            continue;
+        };
+        println!("cargo:rerun-if-changed={}", path.join("Cargo.toml").display());
+        let manifest = fs::read_to_string(path.join("Cargo.toml")).unwrap();
+        let name = manifest_value(&manifest, "package", "name").unwrap_or(&dir).to_string();
+        // crate names need to be unique, so other years can add the year
+        if name != dir && name != format!("{dir}_{year}") {
+            errors.push(format!("crate {name} should be named after its directory {dir}, or {dir}_{year}"));
+            continue;
+        }
+        if manifest_value(aoc_manifest, "dependencies", &name).is_none() {
+            errors.push(format!("{name} is missing from the dependencies of aoc (`aoc new` adds them for new days), add:\n    \
+                {name} = {{ path = \"../{reldir}\" }}"));
+            continue;
+        }
+        // a wrong solver name, or a missing solve function, is a compile error of the generated table
+        let day = match manifest_value(&manifest, "package.metadata.aoc", "solver") {
+            Some(t) => format!("        Day::solver::<{name}::{t}>(\"{reldir}\"),\n"),
+            None => format!("        Day{{ dir: \"{reldir}\", solve: {name}::solve }},\n"),
+        };
+        if let Some(prev) = days.insert(num, day) {
+            errors.push(format!("duplicate puzzle number {num} in {reldir} and {}", prev.split('"').nth(1).unwrap()));
+        }
+    }
+    days
+}
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let aoc_dir = Path::new(&manifest_dir);
+    let root = aoc_dir.parent().unwrap();
+    let aoc_manifest = fs::read_to_string(aoc_dir.join("Cargo.toml")).unwrap();
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed=../Cargo.toml");
+    // a new day in the workspace shows up in the lock file
+    println!("cargo:rerun-if-changed=../Cargo.lock");
+
+    // the days in the root directory are for the year in the package metadata, other years have their own directory
+    let default_year: u16 = manifest_value(&aoc_manifest, "package.metadata.aoc", "year").and_then(|y| y.parse().ok())
+        .expect("need the year of the puzzles in [package.metadata.aoc] in Cargo.toml");
+    let mut errors = Vec::new();
+    let mut years = BTreeMap::new();
+    years.insert(default_year, scan_days(root, "", default_year, &aoc_manifest, &mut errors));
+    for entry in fs::read_dir(root).unwrap() {
+        let path = entry.unwrap().path();
+        let dir = path.file_name().unwrap().to_string_lossy().to_string();
+        let Some(year) = dir.parse::<u16>().ok().filter(|_| dir.len() == 4 && path.is_dir()) else {
+            continue;
+        };
+        if year == default_year {
+            errors.push(format!("the days of {year} should be in the root directory, not in {dir}"));
+            continue;
+        }
+        let days = scan_days(root, &dir, year, &aoc_manifest, &mut errors);
+        if !days.is_empty() {
+            years.insert(year, days);
+        }
+    }
+    if !errors.is_empty() {
+        for e in &errors {
+            println!("cargo:warning={e}");
+        }
+        panic!("cannot build the table of puzzle days:\n{}", errors.join("\n"));
+    }
+    if years[&default_year].is_empty() {
+        panic!("no day* directories found in {}", root.display());
+    }
+
+    let mut out = format!("// all puzzle days by year, generated by build.rs\nconst DEFAULT_YEAR: u16 = {default_year};\nconst YEARS: &[Year] = &[\n");
+    for (year, days) in years {
+        out.push_str(&format!("    Year {{ year: {year}, days: &[\n"));
+        out.extend(days.into_values());
+        out.push_str("    ] },\n");
+    }
+    out.push_str("];\n");
+    let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("days.rs");
+    fs::write(dest, out).unwrap();
+}
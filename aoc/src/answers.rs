@@ -0,0 +1,79 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+// Expected answers for an input file are stored next to it, with the extension replaced by ".answers".
+// So the answers for input/input.txt are in input/input.answers, which contains lines like:
+//   part1: 1234
+//   part2: 5678
+// An answer of more than one line is written as "|", followed by the lines of the answer indented:
+//   part2: |
+//     #..#
+//     ####
+// Empty lines and lines starting with # are ignored.
+pub fn answers_file(input: &Path) -> PathBuf {
+    input.with_extension("answers")
+}
+
+// read the expected answers for an input file. If there is no answers file, no answers are known.
+pub fn read_answers(input: &Path) -> io::Result<Vec<Option<String>>> {
+    match fs::read_to_string(answers_file(input)) {
+        Ok(contents) => parse_answers(&contents),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![None, None]),
+        Err(e) => Err(e),
+    }
+}
+
+fn parse_answers(contents: &str) -> io::Result<Vec<Option<String>>> {
+    let mut answers = vec![None, None];
+    // the answer that indented lines are added to, and the indent of its first line
+    let mut block: Option<(&mut String, usize)> = None;
+    for (lnum, line) in contents.lines().enumerate() {
+        if let (Some((b, indent)), true) = (&mut block, line.starts_with([' ', '\t'])) {
+            if b.is_empty() {
+                *indent = line.len() - line.trim_start().len();
+            } else {
+                b.push('\n');
+            }
+            // only the indent is removed, other leading whitespace can be part of the answer
+            let skip = line.len() - line.trim_start().len();
+            b.push_str(line[skip.min(*indent)..].trim_end());
+            continue;
+        }
+        block = None;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let part = line.split_once(':')
+            .and_then(|(p, a)| p.trim().strip_prefix("part").map(|n| (n, a.trim())))
+            .and_then(|(n, a)| n.parse::<usize>().ok().map(|n| (n, a)));
+        match part {
+            Some((n, "|")) if (1..=answers.len()).contains(&n) => block = Some((answers[n-1].insert(String::new()), 0)),
+            Some((n, a)) if (1..=answers.len()).contains(&n) => answers[n-1] = Some(a.to_string()),
+            _ => return Err(io::Error::new(ErrorKind::InvalidData,
+                format!("line {}: expected \"part1: <answer>\" or \"part2: <answer>\", got \"{line}\"", lnum + 1))),
+        }
+    }
+    Ok(answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers_file() {
+        assert_eq!(answers_file(Path::new("day1/input/input.txt")), PathBuf::from("day1/input/input.answers"));
+        assert_eq!(answers_file(Path::new("example")), PathBuf::from("example.answers"));
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        assert_eq!(parse_answers("part1: 142\npart2: 281\n").unwrap(), vec![Some("142".to_string()), Some("281".to_string())]);
+        assert_eq!(parse_answers("# only part 2 is known\n\npart2:  foo bar \n").unwrap(), vec![None, Some("foo bar".to_string())]);
+        assert!(parse_answers("part3: 1\n").is_err());
+        assert!(parse_answers("142\n").is_err());
+        assert_eq!(parse_answers("part1: |\n  #..#\n   ###\npart2: 3\n").unwrap(), vec![Some("#..#\n ###".to_string()), Some("3".to_string())]);
+    }
+}
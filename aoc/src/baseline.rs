@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use exrunner::{ExRunner, BenchStats, Stats};
+
+// name of the file in the root directory that keeps the timing baselines
+pub const BASELINE_FILE: &str = "timing_baseline.json";
+
+// Differences smaller than this are considered noise, and never flagged as a regression
+const NOISE: Duration = Duration::from_micros(1);
+
+// Timings of a single puzzle run, in nanoseconds
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Timings {
+    pub parse_ns: Option<u64>,
+    pub time1_ns: Option<u64>,
+    pub time2_ns: Option<u64>,
+    pub total_ns: Option<u64>,
+}
+
+impl Timings {
+    // timings of a run. When benchmarking, use the median of all runs.
+    pub fn new(er: &ExRunner, bench: Option<&BenchStats>) -> Timings {
+        let ns = |d: Option<Duration>| d.map(|d| d.as_nanos() as u64);
+        let median = |s: Option<Stats>| ns(s.map(|s| s.median));
+        match bench {
+            Some(b) => Timings { parse_ns: median(b.parse), time1_ns: median(b.part1), time2_ns: median(b.part2), total_ns: median(b.total) },
+            None => Timings { parse_ns: ns(er.parsetime()), time1_ns: ns(er.time1()), time2_ns: ns(er.time2()), total_ns: ns(er.totaltime()) },
+        }
+    }
+
+    pub fn stages(&self) -> [(&'static str, Option<u64>); 4] {
+        [("parse", self.parse_ns), ("part1", self.time1_ns), ("part2", self.time2_ns), ("total", self.total_ns)]
+    }
+
+    // Compare against a baseline. For each stage, returns the percentage it got slower, if that is over the threshold.
+    pub fn regressions(&self, baseline: &Timings, threshold: f64) -> [Option<f64>; 4] {
+        let mut result = [None; 4];
+        for (i, ((_, cur), (_, base))) in self.stages().into_iter().zip(baseline.stages()).enumerate() {
+            if let (Some(cur), Some(base)) = (cur, base) {
+                if cur < base + NOISE.as_nanos() as u64 {
+                    continue;
+                }
+                let slower = (cur as f64 / base.max(1) as f64 - 1.0) * 100.0;
+                if slower > threshold {
+                    result[i] = Some(slower);
+                }
+            }
+        }
+        result
+    }
+}
+
+// Baseline timings of all puzzles, stored as JSON in the root directory.
+// The key is the puzzle directory and the input file, as timings depend on the input.
+pub struct Baselines {
+    path: PathBuf,
+    timings: BTreeMap<String, Timings>,
+}
+
+impl Baselines {
+    // load the baselines. If the file does not exist, there are no baselines yet.
+    pub fn load(rootdir: &Path) -> io::Result<Baselines> {
+        let mut path = rootdir.to_path_buf();
+        path.push(BASELINE_FILE);
+        let timings = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Baselines { path, timings })
+    }
+
+    pub fn key(dir: &str, inputfile: &str) -> String {
+        format!("{dir}/{inputfile}")
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Timings> {
+        self.timings.get(key)
+    }
+
+    // set the baseline for a puzzle. Existing baselines are only replaced if overwrite is given.
+    // Returns true if the baseline was set.
+    pub fn set(&mut self, key: &str, timings: Timings, overwrite: bool) -> bool {
+        if !overwrite && self.timings.contains_key(key) {
+            return false;
+        }
+        self.timings.insert(key.to_string(), timings);
+        true
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut contents = serde_json::to_string_pretty(&self.timings)?;
+        contents.push('\n');
+        fs::write(&self.path, contents)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timings(parse: u64, time1: u64, time2: Option<u64>) -> Timings {
+        Timings { parse_ns: Some(parse), time1_ns: Some(time1), time2_ns: time2, total_ns: Some(parse + time1 + time2.unwrap_or(0)) }
+    }
+
+    #[test]
+    fn test_regressions() {
+        let base = timings(100_000, 1_000_000, Some(500_000));
+        assert_eq!(timings(100_000, 1_000_000, Some(500_000)).regressions(&base, 20.0), [None; 4]);
+        let r = timings(90_000, 1_300_000, None).regressions(&base, 20.0);
+        assert_eq!(r[0], None);
+        assert!((r[1].unwrap() - 30.0).abs() < 1e-9);
+        assert_eq!(r[2], None);
+        assert_eq!(r[3], None);
+        // tiny timings do not count as regressions
+        let base = timings(100, 200, None);
+        assert_eq!(timings(200, 400, None).regressions(&base, 20.0), [None; 4]);
+    }
+
+    #[test]
+    fn test_set_and_save() {
+        let dir = std::env::temp_dir().join(format!("aoc-baseline-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut b = Baselines::load(&dir).unwrap();
+        let key = Baselines::key("day1_test", "input.txt");
+        assert!(b.get(&key).is_none());
+        assert!(b.set(&key, timings(1, 2, None), false));
+        assert!(!b.set(&key, timings(3, 4, None), false));
+        assert_eq!(b.get(&key), Some(&timings(1, 2, None)));
+        b.save().unwrap();
+        let mut b = Baselines::load(&dir).unwrap();
+        assert_eq!(b.get(&key), Some(&timings(1, 2, None)));
+        assert!(b.set(&key, timings(3, 4, None), true));
+        assert_eq!(b.get(&key), Some(&timings(3, 4, None)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use exrunner::Level;
+use crate::{Day, first_number, STDIN};
+
+// A --debug option: the level for all puzzles, or for one puzzle as DAY=LEVEL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugSpec {
+    day: Option<u32>,
+    level: Level,
+}
+
+// parse "trace", "12=trace" or "day12=trace"
+pub fn parse_debug_spec(s: &str) -> Result<DebugSpec, String> {
+    match s.split_once('=') {
+        None => Ok(DebugSpec { day: None, level: s.parse()? }),
+        Some((day, level)) => {
+            let day = first_number(day).parse().map_err(|_| format!("no puzzle number in '{day}'"))?;
+            Ok(DebugSpec { day: Some(day), level: level.parse()? })
+        },
+    }
+}
+
+impl fmt::Display for DebugSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.day {
+            Some(day) => write!(f, "{day}={}", self.level),
+            None => write!(f, "{}", self.level),
+        }
+    }
+}
+
+// The debug level of every puzzle, from the number of -v flags and the --debug options
+#[derive(Debug)]
+pub struct DebugFilter {
+    default: Level,
+    days: Vec<(u32, Level)>,
+}
+
+impl DebugFilter {
+    pub fn new(verbose: u8, specs: &[DebugSpec]) -> DebugFilter {
+        let mut filter = DebugFilter { default: Level::from_verbosity(verbose), days: Vec::new() };
+        for spec in specs {
+            match spec.day {
+                Some(day) => filter.days.push((day, spec.level)),
+                None => filter.default = spec.level,
+            }
+        }
+        filter
+    }
+
+    pub fn level(&self, day: &Day) -> Level {
+        // the last option for a day wins
+        self.days.iter().rev().find(|(d, _)| *d == day.number()).map(|(_, l)| *l).unwrap_or(self.default)
+    }
+}
+
+// file to capture the debug output of running a puzzle on an input, like day12_hot_springs.input.txt.log
+pub fn debug_file(dir: &Path, day: &Day, input: &str) -> PathBuf {
+    let input = match Path::new(input).file_name() {
+        _ if input == STDIN => String::from("stdin"),
+        Some(name) => name.to_string_lossy().to_string(),
+        None => input.to_string(),
+    };
+    dir.join(format!("{}.{input}.log", day.dir.replace('/', "_")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exrunner::{ExRunner, PuzzleResult};
+    use crate::Input;
+
+    fn do_nothing(_i: Input, _er: &mut ExRunner) -> PuzzleResult {
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter() {
+        let specs: Vec<_> = ["day12=trace", "debug", "8=off", "12=info"].iter().map(|s| parse_debug_spec(s).unwrap()).collect();
+        assert_eq!(specs[0].to_string(), "12=trace");
+        let filter = DebugFilter::new(0, &specs);
+        assert_eq!(filter.level(&Day { dir: "day12_hot_springs", solve: do_nothing }), Level::Info);
+        assert_eq!(filter.level(&Day { dir: "day8_haunted_wasteland", solve: do_nothing }), Level::Off);
+        assert_eq!(filter.level(&Day { dir: "day1_trebuchet", solve: do_nothing }), Level::Debug);
+        assert_eq!(DebugFilter::new(2, &[]).level(&Day { dir: "day1_trebuchet", solve: do_nothing }), Level::Trace);
+        assert!(parse_debug_spec("x=trace").is_err());
+        assert!(parse_debug_spec("12=loud").is_err());
+    }
+
+    #[test]
+    fn test_debug_file() {
+        let day = Day { dir: "2022/day1_calorie_counting", solve: do_nothing };
+        assert_eq!(debug_file(Path::new("logs"), &day, "./some/path.txt"), Path::new("logs/2022_day1_calorie_counting.path.txt.log"));
+        assert_eq!(debug_file(Path::new("logs"), &day, "-"), Path::new("logs/2022_day1_calorie_counting.stdin.log"));
+    }
+}
@@ -0,0 +1,179 @@
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use crate::answers::answers_file;
+use crate::fetch::{Http, InputProvider, FetchError, write_atomic};
+
+// An example from the puzzle text, with the answers given for it
+#[derive(Debug, PartialEq, Eq)]
+pub struct Example {
+    pub input: String,
+    pub answers: [Option<String>; 2],
+}
+
+// What can be found on the page of a puzzle: examples, and the answers for the real input that were already given.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PuzzlePage {
+    pub examples: Vec<Example>,
+    pub answers: [Option<String>; 2],
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&#39;", "'").replace("&amp;", "&")
+}
+
+// remove tags like <em>, that are used for highlighting
+pub fn strip_tags(s: &str) -> String {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        rest = rest[start..].find('>').map(|end| &rest[start+end+1..]).unwrap_or("");
+    }
+    out.push_str(rest);
+    unescape(&out)
+}
+
+// all pieces of text between start and end
+fn between<'a>(s: &'a str, start: &'a str, end: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    s.split(start).skip(1).filter_map(move |p| p.find(end).map(|e| &p[..e]))
+}
+
+// Extract the examples and answers from the HTML of a puzzle page. Every part is in its own article.
+// The examples are the <pre><code> blocks, and the answer for the example is taken to be the
+// last highlighted code in the part. Answers to the real input follow the article.
+pub fn parse_puzzle_page(html: &str) -> PuzzlePage {
+    let mut page = PuzzlePage { examples: Vec::new(), answers: [None, None] };
+    for (part, chunk) in html.split("<article class=\"day-desc\">").skip(1).take(2).enumerate() {
+        let (article, after) = chunk.split_once("</article>").unwrap_or((chunk, ""));
+        let answer = between(article, "<code><em>", "</em></code>").last().map(strip_tags);
+        let mut new_examples = between(article, "<pre><code>", "</code></pre>")
+            .map(strip_tags)
+            .filter(|input| !page.examples.iter().any(|e| &e.input == input))
+            .map(|input| Example { input, answers: [None, None] })
+            .collect::<Vec<_>>();
+        // the answer belongs to the first example of this part, or else to the first example of the puzzle
+        let target = if new_examples.is_empty() { page.examples.first_mut() } else { new_examples.first_mut() };
+        if let Some(e) = target {
+            e.answers[part] = answer;
+        }
+        page.examples.append(&mut new_examples);
+        page.answers[part] = between(after, "Your puzzle answer was <code>", "</code>").next().map(strip_tags);
+    }
+    page
+}
+
+// file name for the n-th example: example.txt, example2.txt, ...
+pub fn example_name(n: usize) -> String {
+    match n {
+        0 => String::from("example.txt"),
+        _ => format!("example{}.txt", n + 1),
+    }
+}
+
+fn answers_text(answers: &[Option<String>; 2]) -> String {
+    let mut text = String::from("# from the puzzle page\n");
+    for (i, a) in answers.iter().enumerate() {
+        match a {
+            Some(a) if a.contains('\n') => {
+                text.push_str(&format!("part{}: |\n", i + 1));
+                a.lines().for_each(|l| text.push_str(&format!("  {l}\n")));
+            },
+            Some(a) => text.push_str(&format!("part{}: {a}\n", i + 1)),
+            None => (),
+        }
+    }
+    text
+}
+
+// write a file, unless it is already there
+fn write_new(path: &Path, contents: &str, written: &mut Vec<PathBuf>) -> io::Result<()> {
+    if path.exists() {
+        eprintln!("{} already exists, not overwriting", path.to_string_lossy());
+        return Ok(());
+    }
+    write_atomic(path, contents.as_bytes())?;
+    written.push(path.to_path_buf());
+    Ok(())
+}
+
+// Save the examples and their answers in the input directory of a puzzle, together with the answers
+// for the real input. Existing files are left alone. Returns the files written.
+pub fn save_examples(page: &PuzzlePage, inputdir: &Path) -> io::Result<Vec<PathBuf>> {
+    if page.examples.is_empty() {
+        return Err(io::Error::new(ErrorKind::NotFound, "no examples found on the puzzle page"));
+    }
+    let mut written = Vec::new();
+    for (n, example) in page.examples.iter().enumerate() {
+        let fname = inputdir.join(example_name(n));
+        write_new(&fname, &example.input, &mut written)?;
+        if example.answers.iter().any(Option::is_some) {
+            write_new(&answers_file(&fname), &answers_text(&example.answers), &mut written)?;
+        }
+    }
+    if page.answers.iter().any(Option::is_some) {
+        write_new(&answers_file(&inputdir.join("input.txt")), &answers_text(&page.answers), &mut written)?;
+    }
+    Ok(written)
+}
+
+// Download the page of a puzzle, and save the examples in the input directory
+pub fn fetch_examples<H: Http>(provider: &InputProvider<H>, day: u32, inputdir: &Path) -> Result<Vec<PathBuf>, FetchError> {
+    let url = provider.puzzle_url(day);
+    eprintln!("Downloading puzzle from {url}");
+    let html = provider.get(&url)?;
+    let page = parse_puzzle_page(&String::from_utf8_lossy(&html));
+    Ok(save_examples(&page, inputdir)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn s(v: &str) -> Option<String> {
+        Some(v.to_string())
+    }
+
+    #[test]
+    fn test_part1_solved() {
+        let page = parse_puzzle_page(include_str!("../testdata/puzzle_part1.html"));
+        assert_eq!(page.examples, vec![Example { input: "0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45\n".to_string(), answers: [s("114"), None] }]);
+        assert_eq!(page.answers, [s("1992273652"), None]);
+    }
+
+    #[test]
+    fn test_both_solved() {
+        let page = parse_puzzle_page(include_str!("../testdata/puzzle_both.html"));
+        assert_eq!(page.examples.len(), 2);
+        assert_eq!(page.examples[0].answers, [s("142"), None]);
+        assert!(page.examples[1].input.starts_with("two1nine\n"));
+        assert_eq!(page.examples[1].answers, [None, s("281")]);
+        assert_eq!(page.answers, [s("54630"), s("54770")]);
+    }
+
+    #[test]
+    fn test_same_example() {
+        // part 2 uses the example of part 1 again
+        let html = "<article class=\"day-desc\"><pre><code>a &lt;<em>b</em>&gt; &amp;\n</code></pre><code><em>1</em></code></article>\
+            <article class=\"day-desc\"><pre><code>a &lt;b&gt; &amp;\n</code></pre><code><em>2</em></code></article>";
+        let page = parse_puzzle_page(html);
+        assert_eq!(page.examples, vec![Example { input: "a <b> &\n".to_string(), answers: [s("1"), s("2")] }]);
+        assert_eq!(page.answers, [None, None]);
+    }
+
+    #[test]
+    fn test_save_examples() {
+        let dir = std::env::temp_dir().join(format!("aoc-example-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let page = parse_puzzle_page(include_str!("../testdata/puzzle_both.html"));
+        let written = save_examples(&page, &dir).unwrap();
+        assert_eq!(written.len(), 5);
+        assert_eq!(fs::read_to_string(dir.join("example.answers")).unwrap(), "# from the puzzle page\npart1: 142\n");
+        assert_eq!(fs::read_to_string(dir.join("example2.answers")).unwrap(), "# from the puzzle page\npart2: 281\n");
+        assert_eq!(fs::read_to_string(dir.join("input.answers")).unwrap(), "# from the puzzle page\npart1: 54630\npart2: 54770\n");
+        assert!(fs::read_to_string(dir.join("example.txt")).unwrap().starts_with("1abc2\n"));
+        // a second time nothing is overwritten
+        assert!(save_examples(&page, &dir).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
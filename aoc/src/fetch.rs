@@ -0,0 +1,301 @@
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write, ErrorKind};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+// the Advent of Code maintainers ask automated tools to identify themselves
+pub const USER_AGENT: &str = concat!("github.com/jpcornet/AoC2023 aoc runner by ", env!("CARGO_PKG_AUTHORS"));
+
+// Response to a GET request: status code and body
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+// The part of an HTTP client that is needed to talk to the site. A trait, so it can be replaced in tests.
+pub trait Http {
+    // GET the url with the given extra headers. Only errors if there was no response at all.
+    fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<HttpResponse, String>;
+
+    // POST a form to the url
+    fn post(&self, url: &str, headers: &[(&str, &str)], form: &[(&str, &str)]) -> Result<HttpResponse, String>;
+}
+
+// Http using a blocking reqwest client
+pub struct ReqwestHttp(reqwest::blocking::Client);
+
+impl ReqwestHttp {
+    pub fn new() -> Result<ReqwestHttp, FetchError> {
+        reqwest::blocking::Client::builder()
+            .build()
+            .map(ReqwestHttp)
+            .map_err(|e| FetchError::Request { url: String::new(), msg: e.to_string() })
+    }
+}
+
+impl ReqwestHttp {
+    fn send(&self, mut req: reqwest::blocking::RequestBuilder, headers: &[(&str, &str)]) -> Result<HttpResponse, String> {
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let resp = req.send().map_err(|e| e.to_string())?;
+        let status = resp.status().as_u16();
+        let body = resp.bytes().map_err(|e| e.to_string())?.to_vec();
+        Ok(HttpResponse { status, body })
+    }
+}
+
+impl Http for ReqwestHttp {
+    fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<HttpResponse, String> {
+        self.send(self.0.get(url), headers)
+    }
+
+    fn post(&self, url: &str, headers: &[(&str, &str)], form: &[(&str, &str)]) -> Result<HttpResponse, String> {
+        self.send(self.0.post(url).form(form), headers)
+    }
+}
+
+// Why fetching something failed
+#[derive(Debug)]
+pub enum FetchError {
+    NoSession(io::Error),
+    Request { url: String, msg: String },
+    Status { url: String, status: u16 },
+    Io(io::Error),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::NoSession(e) => write!(f, "no session cookie found: {e}"),
+            FetchError::Request { url, msg } => write!(f, "cannot download from {url}: {msg}"),
+            FetchError::Status { url, status } => write!(f, "error downloading from {url}: status {status}"),
+            FetchError::Io(e) => write!(f, "cannot write input: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl From<io::Error> for FetchError {
+    fn from(e: io::Error) -> FetchError {
+        FetchError::Io(e)
+    }
+}
+
+// Get the session cookie from the SESSION_COOKIE environment variable, or the session.cookie file in the root dir
+pub fn get_session_cookie(rootdir: &Path) -> io::Result<String> {
+    if let Ok(s) = env::var("SESSION_COOKIE") {
+        return Ok(s);
+    }
+    let mut cookiefile = rootdir.to_path_buf();
+    cookiefile.push("session.cookie");
+    let mut fh = File::open(cookiefile)?;
+    let mut contents = String::new();
+    fh.read_to_string(&mut contents)?;
+    Ok(contents.trim().to_string())
+}
+
+// Fetches puzzle inputs from the Advent of Code site, or from another server with the same layout.
+pub struct InputProvider<H: Http> {
+    http: H,
+    base_url: String,
+    session: String,
+    year: u16,
+    // number of extra attempts after a failure that might be temporary
+    pub retries: u32,
+    // wait before the first retry, doubled for every next one
+    pub backoff: Duration,
+}
+
+impl InputProvider<ReqwestHttp> {
+    // Provider for the real site, or the one in the AOC_BASE_URL environment variable
+    pub fn new(rootdir: &Path, year: u16) -> Result<InputProvider<ReqwestHttp>, FetchError> {
+        let session = get_session_cookie(rootdir).map_err(FetchError::NoSession)?;
+        let base_url = env::var("AOC_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Ok(InputProvider::with_http(ReqwestHttp::new()?, &base_url, &session, year))
+    }
+}
+
+impl<H: Http> InputProvider<H> {
+    pub fn with_http(http: H, base_url: &str, session: &str, year: u16) -> InputProvider<H> {
+        InputProvider {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            year,
+            retries: 3,
+            backoff: Duration::from_secs(1),
+        }
+    }
+
+    pub fn puzzle_url(&self, day: u32) -> String {
+        format!("{}/{}/day/{day}", self.base_url, self.year)
+    }
+
+    pub fn input_url(&self, day: u32) -> String {
+        format!("{}/input", self.puzzle_url(day))
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    // GET a url, retrying when the server cannot be reached or has a temporary problem
+    pub fn get(&self, url: &str) -> Result<Vec<u8>, FetchError> {
+        let cookie = format!("session={}", self.session);
+        let headers = [("Cookie", cookie.as_str()), ("User-Agent", USER_AGENT)];
+        let mut wait = self.backoff;
+        let mut attempt = 0;
+        loop {
+            let err = match self.http.get(url, &headers) {
+                Ok(resp) if (200..300).contains(&resp.status) => return Ok(resp.body),
+                Ok(resp) => FetchError::Status { url: url.to_string(), status: resp.status },
+                Err(msg) => FetchError::Request { url: url.to_string(), msg },
+            };
+            // client errors will not go away by asking again, except for "too many requests"
+            let temporary = match err {
+                FetchError::Status { status, .. } => status == 429 || status >= 500,
+                _ => true,
+            };
+            if !temporary || attempt >= self.retries {
+                return Err(err);
+            }
+            eprintln!("{err}, retrying in {wait:?}");
+            thread::sleep(wait);
+            wait *= 2;
+            attempt += 1;
+        }
+    }
+
+    // POST a form to a url. Never retried, as the server may have acted on it anyway.
+    pub fn post(&self, url: &str, form: &[(&str, &str)]) -> Result<Vec<u8>, FetchError> {
+        let cookie = format!("session={}", self.session);
+        let headers = [("Cookie", cookie.as_str()), ("User-Agent", USER_AGENT)];
+        match self.http.post(url, &headers, form) {
+            Ok(resp) if (200..300).contains(&resp.status) => Ok(resp.body),
+            Ok(resp) => Err(FetchError::Status { url: url.to_string(), status: resp.status }),
+            Err(msg) => Err(FetchError::Request { url: url.to_string(), msg }),
+        }
+    }
+
+    // Download the input of a puzzle to the target file. The file only appears once the download is complete.
+    pub fn fetch_input(&self, day: u32, target: &Path) -> Result<(), FetchError> {
+        let url = self.input_url(day);
+        eprintln!("Downloading input from {url}");
+        let body = self.get(&url)?;
+        write_atomic(target, &body)?;
+        Ok(())
+    }
+}
+
+// Write a file by writing a temporary file next to it first, and renaming that when done.
+// Creates the directory if needed.
+pub fn write_atomic(target: &Path, contents: &[u8]) -> io::Result<()> {
+    let targetdir = target.parent().ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "no directory for target"))?;
+    if !targetdir.as_os_str().is_empty() && !targetdir.is_dir() {
+        eprintln!("Creating input directory {}", targetdir.to_string_lossy());
+        fs::create_dir_all(targetdir)?;
+    }
+    let fname = target.file_name().ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "no file name for target"))?;
+    let tmp = targetdir.join(format!(".{}.{}.tmp", fname.to_string_lossy(), std::process::id()));
+    let result = File::create(&tmp)
+        .and_then(|mut fh| { fh.write_all(contents)?; fh.sync_all() })
+        .and_then(|_| fs::rename(&tmp, target));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    // Serve the given responses, one per connection, and record the requests
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut conn, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(conn.try_clone().unwrap());
+                let mut request = String::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    request.push_str(&line);
+                }
+                seen.lock().unwrap().push(request);
+                write!(conn, "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len()).unwrap();
+            }
+        });
+        (base_url, requests)
+    }
+
+    fn provider(base_url: &str) -> InputProvider<ReqwestHttp> {
+        // no proxy, the test server is local
+        let client = reqwest::blocking::Client::builder().no_proxy().build().unwrap();
+        let mut p = InputProvider::with_http(ReqwestHttp(client), base_url, "abc123", 2023);
+        p.backoff = Duration::from_millis(1);
+        p
+    }
+
+    fn tempdir(name: &str) -> std::path::PathBuf {
+        let dir = env::temp_dir().join(format!("aoc-fetch-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_fetch_input() {
+        let (base_url, requests) = serve(vec![(500, "oops"), (200, "1 2 3\n")]);
+        let dir = tempdir("ok");
+        let target = dir.join("input").join("input.txt");
+        provider(&base_url).fetch_input(7, &target).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "1 2 3\n");
+        // only the input is left behind
+        assert_eq!(fs::read_dir(target.parent().unwrap()).unwrap().count(), 1);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("GET /2023/day/7/input "), "{}", requests[1]);
+        let lower = requests[1].to_ascii_lowercase();
+        assert!(lower.contains("cookie: session=abc123"), "{}", requests[1]);
+        assert!(lower.contains("user-agent: github.com/jpcornet/aoc2023"), "{}", requests[1]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_fails() {
+        // not found is not retried, and leaves no file
+        let (base_url, requests) = serve(vec![(404, "not yet")]);
+        let dir = tempdir("notfound");
+        let target = dir.join("input.txt");
+        match provider(&base_url).fetch_input(25, &target) {
+            Err(FetchError::Status { status: 404, .. }) => (),
+            other => panic!("expected status 404, got {other:?}"),
+        }
+        assert!(!target.exists());
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        // server errors are retried, until the retries run out
+        let (base_url, requests) = serve(vec![(503, ""), (502, ""), (500, "")]);
+        let mut p = provider(&base_url);
+        p.retries = 2;
+        assert!(matches!(p.fetch_input(25, &target), Err(FetchError::Status { status: 500, .. })));
+        assert_eq!(requests.lock().unwrap().len(), 3);
+        assert!(!dir.exists() || fs::read_dir(&dir).unwrap().count() == 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,125 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use glob::Pattern;
+
+pub const DEFAULT_INPUT: &str = "input.txt";
+// input name to read from stdin
+pub const STDIN: &str = "-";
+
+// Which files in the input directory of a puzzle to run it against
+#[derive(Debug)]
+pub enum InputSelection {
+    // input.txt, downloaded if missing
+    Default,
+    // a file in the input directory given by name, never downloaded
+    File(String),
+    // a file anywhere, given by a path relative to the current directory
+    Path(PathBuf),
+    // standard input, read once and used for every puzzle
+    Stdin,
+    // all files matching a pattern. input.txt is downloaded if missing, and it matches.
+    Matching(Pattern),
+}
+
+impl InputSelection {
+    pub fn new(input: Option<&str>, all_inputs: Option<&Pattern>) -> InputSelection {
+        match (input, all_inputs) {
+            (_, Some(pat)) => InputSelection::Matching(pat.clone()),
+            (Some(STDIN), None) => InputSelection::Stdin,
+            // a name with a directory in it is a path
+            (Some(f), None) if f.contains('/') || f.contains(MAIN_SEPARATOR) => InputSelection::Path(PathBuf::from(f)),
+            (Some(f), None) => InputSelection::File(f.to_string()),
+            (None, None) => InputSelection::Default,
+        }
+    }
+
+    // true if a puzzle can run against more than one input
+    pub fn is_multiple(&self) -> bool {
+        matches!(self, InputSelection::Matching(_))
+    }
+
+    // may this input be downloaded when it is missing
+    pub fn can_fetch(&self, name: &str) -> bool {
+        name == DEFAULT_INPUT && matches!(self, InputSelection::Default | InputSelection::Matching(_))
+    }
+
+    // the file to read a selected input from, or None for stdin
+    pub fn path(&self, inputdir: &Path, name: &str) -> Option<PathBuf> {
+        match self {
+            InputSelection::Stdin => None,
+            InputSelection::Path(p) => Some(p.clone()),
+            _ => Some(inputdir.join(name)),
+        }
+    }
+
+    // The names of the selected inputs in the input directory, sorted. Answer files and hidden files
+    // (like partial downloads) are never inputs. A missing input.txt is included if selected, so it can be downloaded.
+    pub fn files(&self, inputdir: &Path) -> io::Result<Vec<String>> {
+        let pat = match self {
+            InputSelection::Default => return Ok(vec![DEFAULT_INPUT.to_string()]),
+            InputSelection::File(f) => return Ok(vec![f.clone()]),
+            InputSelection::Path(p) => return Ok(vec![p.to_string_lossy().to_string()]),
+            InputSelection::Stdin => return Ok(vec![STDIN.to_string()]),
+            InputSelection::Matching(pat) => pat,
+        };
+        let mut names = Vec::new();
+        let entries = match fs::read_dir(inputdir) {
+            Ok(entries) => entries.collect::<io::Result<Vec<_>>>()?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.file_type()?.is_file() && !name.starts_with('.') && !name.ends_with(".answers") && pat.matches(&name) {
+                names.push(name);
+            }
+        }
+        if pat.matches(DEFAULT_INPUT) && !names.iter().any(|n| n == DEFAULT_INPUT) {
+            names.push(DEFAULT_INPUT.to_string());
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files() {
+        let dir = std::env::temp_dir().join(format!("aoc-inputs-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("subdir")).unwrap();
+        for f in ["example.txt", "example.answers", "example2.txt", "stress.txt", ".input.txt.123.tmp"] {
+            fs::write(dir.join(f), "").unwrap();
+        }
+        let all = InputSelection::new(None, Some(&Pattern::new("*").unwrap()));
+        assert!(all.is_multiple());
+        assert_eq!(all.files(&dir).unwrap(), vec!["example.txt", "example2.txt", "input.txt", "stress.txt"]);
+        let examples = InputSelection::new(None, Some(&Pattern::new("example*").unwrap()));
+        assert_eq!(examples.files(&dir).unwrap(), vec!["example.txt", "example2.txt"]);
+        assert!(examples.files(&dir.join("nothing")).unwrap().is_empty());
+        assert_eq!(all.files(&dir.join("nothing")).unwrap(), vec![DEFAULT_INPUT]);
+        let one = InputSelection::new(Some("stress.txt"), None);
+        assert_eq!(one.files(&dir).unwrap(), vec!["stress.txt"]);
+        assert!(!one.can_fetch("stress.txt") && !InputSelection::File(DEFAULT_INPUT.to_string()).can_fetch(DEFAULT_INPUT));
+        assert!(all.can_fetch(DEFAULT_INPUT) && InputSelection::Default.can_fetch(DEFAULT_INPUT));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_path() {
+        let inputdir = Path::new("/aoc/day7/input");
+        let one = InputSelection::new(Some("stress.txt"), None);
+        assert_eq!(one.path(inputdir, "stress.txt"), Some(inputdir.join("stress.txt")));
+        let path = InputSelection::new(Some("./some/path.txt"), None);
+        assert_eq!(path.files(inputdir).unwrap(), vec!["./some/path.txt"]);
+        assert_eq!(path.path(inputdir, "./some/path.txt"), Some(PathBuf::from("./some/path.txt")));
+        assert!(!path.can_fetch(DEFAULT_INPUT));
+        let stdin = InputSelection::new(Some(STDIN), None);
+        assert_eq!(stdin.files(inputdir).unwrap(), vec![STDIN]);
+        assert_eq!(stdin.path(inputdir, STDIN), None);
+    }
+}
@@ -1,17 +1,45 @@
 use std::collections::{HashMap, hash_map::Entry};
-use std::io::{Read, BufReader, ErrorKind};
-use std::fs::File;
-use std::path::PathBuf;
+use std::io::{self, Read, Write, BufRead, BufWriter, ErrorKind};
+use std::sync::mpsc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::path::{Path, PathBuf};
 use std::{fs, env};
 use std::os::unix::fs::MetadataExt;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::process::exit;
-use reqwest;
-use clap::{Args, Parser};
+use std::fs::File;
+use clap::{Args, Parser, Subcommand};
 use comfy_table::Table;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::modifiers::UTF8_ROUND_CORNERS;
-use exrunner::{ExRunner, duration_format, ExCtx};
+use exrunner::{ExRunner, PuzzleResult, Solver, solve_with, duration_format, bytes_format, Verdict, BenchStats, Level};
+
+mod answers;
+use answers::read_answers;
+mod report;
+use report::{DayReport, write_json, write_csv};
+mod baseline;
+use baseline::{Baselines, Timings};
+mod runner;
+use runner::{RunOpts, RunError, DayRun, DebugBuf, InputSource, run_isolated};
+mod scaffold;
+mod fetch;
+pub use fetch::{InputProvider, FetchError};
+mod example;
+pub use example::fetch_examples;
+mod submit;
+mod inputs;
+use inputs::InputSelection;
+pub use inputs::STDIN;
+mod debug;
+use debug::{DebugSpec, DebugFilter, parse_debug_spec, debug_file};
+mod progress;
+mod watch;
+pub use watch::watch;
+use submit::Ledger;
+pub use submit::{Outcome, SubmitError};
+pub use scaffold::new_day;
 
 /// command line tool to run Advent of Code puzzles and display output and timings
 ///
@@ -19,46 +47,238 @@ use exrunner::{ExRunner, duration_format, ExCtx};
 /// one given on the command line, or the one in the subdirectory where you are.
 /// Will give "raw" output for individual puzzles or present the results in a table,
 /// together with timing info.
+/// Answers are checked against the expected answers in the .answers file next to the
+/// input file, if present. Exits with a non-zero status if any answer is wrong.
+/// Puzzles that panic or time out are reported as failed, and the other puzzles still run.
+/// Missing inputs are downloaded, using the session cookie from $SESSION_COOKIE or the
+/// session.cookie file. Set $AOC_BASE_URL to download from another server.
 #[derive(Parser, Debug)]
-#[command(author, version = None)]
+#[command(author, version = None, args_conflicts_with_subcommands = true)]
 pub struct CliArgs {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Run all puzzles
     #[arg(short, long)]
     pub all: bool,
 
-    /// input file name (default: input.txt)
+    /// input file name in the input directory (default: input.txt), a path, or - for stdin
     #[arg(short, long)]
     pub input: Option<String>,
 
+    /// Run against every file in the input directory, or only the ones matching GLOB
+    #[arg(long, value_name = "GLOB", num_args = 0..=1, require_equals = true, default_missing_value = "*",
+        value_parser = parse_pattern, conflicts_with = "input")]
+    pub all_inputs: Option<glob::Pattern>,
+
+    /// Benchmark: run each puzzle N times, and report timing statistics
+    #[arg(short, long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub bench: Option<u32>,
+
+    /// Number of warm-up runs before benchmarking
+    #[arg(long, value_name = "N", default_value_t = 3, requires = "bench")]
+    pub warmup: u32,
+
+    /// Save the timings as baseline, for puzzles that do not have a baseline yet
+    #[arg(long)]
+    pub save_baseline: bool,
+
+    /// Save the timings as baseline, replacing existing baselines
+    #[arg(long)]
+    pub overwrite_baseline: bool,
+
+    /// Flag timings that are more than PERCENT slower than the baseline
+    #[arg(long, value_name = "PERCENT", default_value_t = 20.0)]
+    pub threshold: f64,
+
+    /// Give up on a puzzle after SECONDS, and continue with the next one. A puzzle that does not stop
+    /// when asked keeps running in the background, which can skew the timings of the puzzles after it.
+    #[arg(long, value_name = "SECONDS", value_parser = parse_timeout)]
+    pub timeout: Option<Duration>,
+
+    /// Run up to N puzzles in parallel. Output stays in order, but timings get less reliable,
+    /// so it cannot be used with --bench.
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with = "bench")]
+    pub jobs: u32,
+
+    /// Only solve part N of the puzzle(s). Timings are not compared against the baseline.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..=2),
+        conflicts_with_all = ["save_baseline", "overwrite_baseline"])]
+    pub part: Option<u8>,
+
     #[command(flatten)]
     format: OutputFormat,
 
+    /// More debug output of the puzzles: -v for debug, -vv for trace. By default only info is shown.
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Debug level for all puzzles, or for one puzzle as DAY=LEVEL, like day12=trace.
+    /// The levels are off, info, debug and trace.
+    #[arg(long, value_name = "[DAY=]LEVEL", value_parser = parse_debug_spec)]
+    pub debug: Vec<DebugSpec>,
+
+    /// Write the debug output of every puzzle run to its own file in DIR, instead of to stderr
+    #[arg(long, value_name = "DIR")]
+    pub debug_dir: Option<PathBuf>,
+
+    /// Do not show the progress of long running puzzles
+    #[arg(long)]
+    pub no_progress: bool,
+
+    /// Rebuild and run the puzzle again whenever its source or input changes
+    #[arg(short, long, conflicts_with_all = ["all", "all_inputs", "bench", "jobs", "save_baseline", "overwrite_baseline"])]
+    pub watch: bool,
+
+    /// Also write the results as JSON to this file. Used by --watch.
+    #[arg(long, hide = true)]
+    pub report_file: Option<PathBuf>,
+
+    /// Year of the puzzles. Default is the year directory you are in, or else the year of the puzzles
+    /// in the root directory.
+    #[arg(long, global = true)]
+    pub year: Option<u16>,
+
     /// which puzzle(s) to run
     pub puzzle: Vec<u32>,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create a crate for a new puzzle, like "aoc new 17 'clumsy crucible'"
+    New {
+        /// puzzle number
+        #[arg(value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+        /// puzzle name, used in the directory name
+        name: String,
+    },
+    /// Download the examples from the puzzle page to input/example*.txt, with the answers given.
+    /// Run them with "aoc N -i example.txt".
+    FetchExample {
+        /// puzzle number
+        day: u32,
+    },
+    /// Solve a part of a puzzle and submit the answer. Answers that are known to be wrong are
+    /// not submitted again, see submissions.json.
+    Submit {
+        /// puzzle number
+        day: u32,
+        /// part to submit, 1 or 2
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: u8,
+    },
+}
+
+fn parse_pattern(s: &str) -> Result<glob::Pattern, String> {
+    glob::Pattern::new(s).map_err(|e| e.to_string())
+}
+
+fn parse_timeout(s: &str) -> Result<Duration, String> {
+    match s.parse::<f64>() {
+        Ok(secs) if secs > 0.0 && secs.is_finite() => Ok(Duration::from_secs_f64(secs)),
+        _ => Err(format!("expected a positive number of seconds, got \"{s}\"")),
+    }
+}
+
 #[derive(Args, Debug)]
 #[group(required = false, multiple = false)]
 struct OutputFormat {
-    /// Output raw. Default unless --all is given.
+    /// Output raw. Default unless --all or --all-inputs is given.
     #[arg(short, long)]
     raw: bool,
 
     /// Output in table form.
     #[arg(short, long, group="format")]
     table: bool,
+
+    /// Output as JSON, with durations in nanoseconds.
+    #[arg(short, long)]
+    json: bool,
+
+    /// Output as CSV, with durations in nanoseconds.
+    #[arg(short, long)]
+    csv: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Format {
+    Raw,
+    Table,
+    Json,
+    Csv,
+}
+
+impl OutputFormat {
+    // determine output format. Default is raw, or table if many puzzles or inputs are run
+    fn format(&self, many: bool) -> Format {
+        if self.raw {
+            Format::Raw
+        } else if self.table {
+            Format::Table
+        } else if self.json {
+            Format::Json
+        } else if self.csv {
+            Format::Csv
+        } else if many {
+            Format::Table
+        } else {
+            Format::Raw
+        }
+    }
 }
 
+// Input to the puzzles. Boxed, so it can come from a file or from an in-memory buffer.
+// Send, so puzzles can run in parallel.
+pub type Input = Box<dyn BufRead + Send>;
+
 #[derive(Clone)]
 pub struct Day {
     pub dir: &'static str,
-    // Need to specify the specific type of BufReader<File> here, because function
+    // Need to specify the specific type of Input here, because function
     // pointers to generic functions do not exist.
-    pub solve: fn(BufReader<File>, &mut ExRunner),
+    pub solve: fn(Input, &mut ExRunner) -> PuzzleResult,
+}
+
+impl Day {
+    // a day that is solved by a Solver, instead of a solve function
+    pub const fn solver<S: Solver>(dir: &'static str) -> Day {
+        Day { dir, solve: solve_with::<S, Input> }
+    }
+
+    // the puzzle number, the first number in the directory name. Not in the year directory, if any.
+    pub fn number(&self) -> u32 {
+        day_number(self.dir).parse().unwrap_or_else(|_| panic!("Cannot find puzzle number in {}", self.dir))
+    }
+}
+
+// The puzzles of a year. Other years than the default are in a directory named after the year,
+// and that directory is part of the dir of their days.
+#[derive(Clone, Copy)]
+pub struct Year {
+    pub year: u16,
+    pub days: &'static [Day],
+}
+
+// Find the puzzles of the requested year. Without a year, use the year directory you are in, if any,
+// or else the default year.
+pub fn select_year(years: &'static [Year], year: Option<u16>, default_year: u16, rootdir: &Path) -> Result<&'static Year, String> {
+    let year = match year {
+        Some(y) => y,
+        None => env::current_dir().ok()
+            .and_then(|cwd| cwd.strip_prefix(rootdir).ok().and_then(|rel| rel.components().next())
+                .and_then(|c| c.as_os_str().to_str()?.parse::<u16>().ok()))
+            .unwrap_or(default_year),
+    };
+    years.iter().find(|y| y.year == year).ok_or_else(|| {
+        let known: Vec<_> = years.iter().map(|y| y.year.to_string()).collect();
+        format!("no puzzles for {year}, only for {}", known.join(", "))
+    })
 }
 
 // returns the first number in a string
-fn first_number<'a>(input: &'a str) -> &'a str {
+pub fn first_number(input: &str) -> &str {
     let start_off = input.find(|c: char| c.is_ascii_digit());
     if start_off.is_none() {
         return "";
@@ -68,14 +288,17 @@ fn first_number<'a>(input: &'a str) -> &'a str {
     &input[start_off..start_off+end_off]
 }
 
+// the puzzle number from a puzzle directory, which may be in a year directory
+fn day_number(dir: &str) -> &str {
+    first_number(dir.rsplit('/').next().unwrap_or(dir))
+}
+
 // convert list of puzzle numbers to Vec of Day structures.
 pub fn to_days(puzzle: &Vec<u32>, days: &[Day]) -> Vec<Day> {
-    // keep hash of puzzle number and index
+    // keep hash of puzzle number and index. Puzzle numbers are unique, the build checks that.
     let mut puzzle_pos: HashMap<u32, Option<usize>> = HashMap::new();
     for (index, d) in days.iter().enumerate() {
-        let puzzlenum: u32 = first_number(d.dir).parse().expect(&format!("Cannot find puzzle number in {}", d.dir));
-        assert!(!puzzle_pos.contains_key(&puzzlenum), "Duplicate puzzle number");
-        puzzle_pos.insert(puzzlenum, Some(index));
+        puzzle_pos.insert(d.number(), Some(index));
     }
     let mut result: Vec<Day> = Vec::new();
     for p in puzzle {
@@ -92,7 +315,7 @@ pub fn to_days(puzzle: &Vec<u32>, days: &[Day]) -> Vec<Day> {
 pub fn current_puzzle(days: &'static [Day]) -> std::io::Result<&'static [Day]> {
     let curdir = env::current_dir()?;
     let curdir_str = curdir.to_string_lossy() + "/";
-    for (index, d) in days.into_iter().enumerate() {
+    for (index, d) in days.iter().enumerate() {
         if curdir_str.contains(&format!("/{}/", d.dir)) {
             return Ok(&days[index..=index]);
         }
@@ -100,123 +323,444 @@ pub fn current_puzzle(days: &'static [Day]) -> std::io::Result<&'static [Day]> {
     Err(std::io::Error::new(ErrorKind::NotFound, "Current directory is not a puzzle"))
 }
 
-// run a list of puzzles
-pub fn run_puzzles(rootdir: PathBuf, args: &CliArgs, days: &[Day], year: u16) {
-    let defaultinput = String::from("input.txt");
-    let inputfile  = args.input.as_ref().unwrap_or(&defaultinput);
-    // determine output format, raw or table
-    let f_raw;
-    let f_table;
-    if args.format.raw || args.format.table {
-        f_raw = args.format.raw;
-        f_table = args.format.table;
-    } else if args.all {
-        f_table = true;
-        f_raw = false;
-    } else {
-        f_table = false;
-        f_raw = true;
+// Why the input of a puzzle is not there. Kept as text, as stdin is shared by all puzzles.
+#[derive(Clone, Debug)]
+enum InputError {
+    Fetch(String),
+    Read(ErrorKind, String),
+}
+
+// A puzzle that is ready to run: the input file is there, and the expected answers are read.
+// Or the input could not be fetched or read, then it only needs reporting.
+struct Job<'a> {
+    day: &'a Day,
+    // name of the input file, and where to read it
+    input: String,
+    source: InputSource,
+    expected: Vec<Option<String>>,
+    input_error: Option<InputError>,
+    level: Level,
+}
+
+impl Job<'_> {
+    fn run(&self, opts: RunOpts, debug: Option<Box<dyn Write + Send>>) -> Result<DayRun, RunError> {
+        match &self.input_error {
+            Some(InputError::Fetch(e)) => Err(RunError::Fetch(e.clone())),
+            Some(InputError::Read(kind, e)) => Err(RunError::Io(io::Error::new(*kind, e.clone()))),
+            None => run_isolated(self.day, &self.source, RunOpts { level: self.level, ..opts }, debug),
+        }
     }
-    let mut table = Table::new();
-    if f_table {
-        table.load_preset(UTF8_FULL)
-            .apply_modifier(UTF8_ROUND_CORNERS);
+
+    // Where the debug output goes: a file per run with --debug-dir, or else the given writer, if any
+    fn debug_writer(&self, args: &CliArgs, writer: Option<Box<dyn Write + Send>>) -> Option<Box<dyn Write + Send>> {
+        let Some(dir) = &args.debug_dir else {
+            return writer;
+        };
+        let path = debug_file(dir, self.day, &self.input);
+        match File::create(&path) {
+            Ok(fh) => Some(Box::new(BufWriter::new(fh))),
+            Err(e) => {
+                eprintln!("Warning: cannot write debug output to {}: {e}", path.to_string_lossy());
+                None
+            },
+        }
     }
-    table.set_header(vec!["", "part1", "part2", "parse", "time1", "time2", "close"]);
-    let mut total_time = Duration::from_secs(0);
-    for (index, d) in days.iter().enumerate() {
-        let mut fname = rootdir.clone();
-        fname.push(d.dir);
-        fname.push("input");
-        fname.push(inputfile);
+}
+
+// run a list of puzzles. Returns false if any of the answers failed verification, or a puzzle failed to run.
+pub fn run_puzzles(rootdir: PathBuf, args: &CliArgs, days: &[Day], year: u16) -> bool {
+    let selection = InputSelection::new(args.input.as_deref(), args.all_inputs.as_ref());
+    let format = args.format.format(args.all || selection.is_multiple());
+    let mut reporter = Reporter::new(&rootdir, args, format, selection.is_multiple());
+    let filter = DebugFilter::new(args.verbose, &args.debug);
+    if let Some(dir) = &args.debug_dir {
+        fs::create_dir_all(dir).unwrap_or_else(|e| {
+            eprintln!("Error: cannot create debug directory {}: {e}", dir.to_string_lossy());
+            exit(1);
+        });
+    }
+    // stdin can only be read once, so keep it for all puzzles
+    let stdin = matches!(selection, InputSelection::Stdin).then(|| {
+        let mut buf = Vec::new();
+        match io::stdin().read_to_end(&mut buf) {
+            Ok(_) => Ok(InputSource::Data(buf.into())),
+            Err(e) => Err(InputError::Read(e.kind(), e.to_string())),
+        }
+    });
+    // first make sure all inputs are there, downloading them if needed
+    let mut jobs = Vec::new();
+    let mut provider = None;
+    for (d, input) in days.iter().flat_map(|d| day_inputs(&rootdir, d, &selection).into_iter().map(move |i| (d, i))) {
+        let Some(fname) = selection.path(&rootdir.join(d.dir).join("input"), &input) else {
+            let (source, input_error) = match stdin.clone().unwrap() {
+                Ok(source) => (source, None),
+                Err(e) => (InputSource::Data(Vec::new().into()), Some(e)),
+            };
+            jobs.push(Job { day: d, input, source, expected: Vec::new(), input_error, level: filter.level(d) });
+            continue;
+        };
         let meta = fs::metadata(&fname);
+        let mut input_error = None;
         match meta {
-            Err(e) if e.kind() == ErrorKind::NotFound && args.input.is_none() => download_input(&rootdir, &d.dir, &fname, year),
-            Err(e) => panic!("Error fetching {}: {e}", fname.to_string_lossy()),
+            Err(e) if e.kind() == ErrorKind::NotFound && selection.can_fetch(&input) => {
+                let p = provider.get_or_insert_with(|| InputProvider::new(&rootdir, year).map_err(|e| e.to_string()));
+                input_error = match p {
+                    Ok(p) => p.fetch_input(d.number(), &fname).err().map(|e| InputError::Fetch(e.to_string())),
+                    Err(e) => Some(InputError::Fetch(e.clone())),
+                };
+            },
+            // other inputs that cannot be read are reported as errors when running the puzzle
+            Err(_) => (),
             Ok(m) if !m.is_file() => panic!("{} is not a file, but a {:?}", fname.to_string_lossy(), m),
             _ => (),
         };
-        let fh = File::open(&fname);
-        if let Err(e) = fh {
-            eprintln!("Error: cannot open file {} for exercise {}: {e}", fname.to_string_lossy(), d.dir);
-            continue;
+        let mut expected = match input_error {
+            Some(_) => Vec::new(),
+            None => read_answers(&fname).unwrap_or_else(|e| {
+                eprintln!("Warning: cannot read expected answers for {}: {e}", fname.to_string_lossy());
+                Vec::new()
+            }),
+        };
+        // the answer to a part that is not run cannot be checked
+        if let Some(p) = args.part {
+            expected.iter_mut().enumerate().filter(|(i, _)| *i + 1 != p as usize).for_each(|(_, a)| *a = None);
         }
-        let mut ct = ExCtx::new(d.solve, BufReader::new(fh.unwrap()));
-        if f_raw {
-            ct.with_stderr();
+        jobs.push(Job { day: d, input, source: InputSource::File(fname), expected, input_error, level: filter.level(d) });
+    }
+    let opts = RunOpts { bench: args.bench, warmup: args.warmup, timeout: args.timeout, part: args.part.map(usize::from),
+        // status lines of puzzles running in parallel would overwrite each other
+        progress: !args.no_progress && args.jobs == 1, ..Default::default() };
+    let start = Instant::now();
+    let workers = (args.jobs as usize).min(jobs.len());
+    if workers <= 1 {
+        for job in &jobs {
+            // show debug output directly, when giving raw output
+            let debug = job.debug_writer(args, (format == Format::Raw).then(|| Box::new(io::stderr()) as Box<dyn Write + Send>));
+            let run = job.run(opts, debug);
+            reporter.add(job, run, Vec::new());
+        }
+    } else {
+        let next = AtomicUsize::new(0);
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|s| {
+            for _ in 0..workers {
+                let tx = tx.clone();
+                let (jobs, next) = (&jobs, &next);
+                s.spawn(move || loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= jobs.len() {
+                        break;
+                    }
+                    let buf = DebugBuf::default();
+                    let debug = jobs[i].debug_writer(args, (format == Format::Raw).then(|| Box::new(buf.clone()) as Box<dyn Write + Send>));
+                    let run = jobs[i].run(opts, debug);
+                    tx.send((i, run, buf.take())).unwrap();
+                });
+            }
+            drop(tx);
+            // report results in order, as soon as all previous puzzles are done
+            let mut done: Vec<Option<_>> = jobs.iter().map(|_| None).collect();
+            let mut reported = 0;
+            for (i, run, debug) in rx {
+                done[i] = Some((run, debug));
+                while let Some((run, debug)) = done.get_mut(reported).and_then(|r| r.take()) {
+                    reporter.add(&jobs[reported], run, debug);
+                    reported += 1;
+                }
+            }
+        });
+    }
+    reporter.finish(jobs.len(), start.elapsed(), workers)
+}
+
+// the names of the selected input files of a puzzle
+fn day_inputs(rootdir: &Path, day: &Day, selection: &InputSelection) -> Vec<String> {
+    let inputdir = rootdir.join(day.dir).join("input");
+    let inputs = selection.files(&inputdir)
+        .unwrap_or_else(|e| panic!("Error reading {}: {e}", inputdir.to_string_lossy()));
+    if inputs.is_empty() {
+        eprintln!("Warning: no matching inputs for {}", day.dir);
+    }
+    inputs
+}
+
+// Solve a part of a puzzle on the real input, and submit the answer
+pub fn submit_answer(rootdir: &Path, day: &Day, part: u8, year: u16) -> Result<Outcome, SubmitError> {
+    let daynum = day.number();
+    let provider = InputProvider::new(rootdir, year).map_err(SubmitError::Fetch)?;
+    let fname = rootdir.join(day.dir).join("input").join("input.txt");
+    if !fname.exists() {
+        provider.fetch_input(daynum, &fname).map_err(SubmitError::Fetch)?;
+    }
+    let opts = RunOpts { part: Some(part as usize), ..Default::default() };
+    let run = run_isolated(day, &InputSource::File(fname), opts, Some(Box::new(io::stderr()))).map_err(|e| SubmitError::NoAnswer(e.to_string()))?;
+    let answer = run.er.answ()[part as usize - 1].clone()
+        .ok_or_else(|| SubmitError::NoAnswer(format!("part {part} was not solved")))?;
+    println!("Submitting answer for day {daynum} part {part}: {answer}");
+    let mut ledger = Ledger::load(rootdir).map_err(SubmitError::Io)?;
+    submit::submit(&provider, &mut ledger, daynum, part, &answer)
+}
+
+const TABLE_HEADER: [&str; 7] = ["", "part1", "part2", "parse", "time1", "time2", "close"];
+
+// Collects the results of running puzzles, and outputs them in the requested format
+struct Reporter<'a> {
+    args: &'a CliArgs,
+    format: Format,
+    // more than one input per puzzle, so rows are labeled with the input
+    multiple_inputs: bool,
+    reports: Vec<DayReport>,
+    table: Table,
+    bench_table: Table,
+    total_time: Duration,
+    failed: usize,
+    failed_puzzles: usize,
+    baselines: Baselines,
+    baselines_changed: bool,
+    count: usize,
+    // some puzzle reported metrics, so the table gets a column for them
+    has_metrics: bool,
+    // answers of more than one line, shown below the table: the puzzle and part, and the answer
+    blocks: Vec<(String, String)>,
+}
+
+impl<'a> Reporter<'a> {
+    fn new(rootdir: &Path, args: &'a CliArgs, format: Format, multiple_inputs: bool) -> Reporter<'a> {
+        let mut table = Table::new();
+        if format == Format::Table {
+            table.load_preset(UTF8_FULL)
+                .apply_modifier(UTF8_ROUND_CORNERS);
+        }
+        table.set_header(TABLE_HEADER);
+        let mut bench_table = Table::new();
+        bench_table.load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_header(vec!["", "", "min", "median", "mean", "stddev", "p95"]);
+        let baselines = Baselines::load(rootdir).unwrap_or_else(|e| {
+            eprintln!("Error: cannot read timing baselines from {}: {e}", rootdir.join(baseline::BASELINE_FILE).to_string_lossy());
+            exit(1);
+        });
+        Reporter {
+            args, format, multiple_inputs, reports: Vec::new(), table, bench_table, total_time: Duration::from_secs(0),
+            failed: 0, failed_puzzles: 0, baselines, baselines_changed: false, count: 0, has_metrics: false,
+            blocks: Vec::new(),
         }
-        let er = ct.do_run(d.dir.to_string());
-        total_time += er.totaltime().unwrap_or(Duration::from_secs(0));
+    }
+
+    // add the result of running a puzzle, with its buffered debug output
+    fn add(&mut self, job: &Job, run: Result<DayRun, RunError>, debug: Vec<u8>) {
+        let d = job.day;
+        let f_raw = self.format == Format::Raw;
         if f_raw {
-            if index > 0 {
+            if self.count > 0 {
                 println!("---");
             }
-            er.print_raw();
+            if self.multiple_inputs {
+                println!("Input: {}", job.input);
+            }
+            if !debug.is_empty() {
+                io::stdout().flush().unwrap_or_default();
+                io::stderr().write_all(&debug).unwrap_or_default();
+            }
         }
-        if f_table {
-            let mut row = vec![d.dir.to_string()];
-            let mut answers: Vec<String> = er.answ().into_iter().map(|x| x.unwrap_or(String::from(""))).collect();
+        self.count += 1;
+        let DayRun { er, bench, timeout } = match run {
+            Ok(run) => run,
+            Err(e) => return self.add_error(job, e),
+        };
+        let args = self.args;
+        self.total_time += er.totaltime().unwrap_or(Duration::from_secs(0));
+        let mut verdicts = er.verify(&job.expected);
+        // a puzzle that was stopped at the timeout failed, but its missing answers are not wrong
+        let timeout = timeout.map(RunError::Timeout);
+        if timeout.is_some() {
+            self.failed_puzzles += 1;
+            for (v, a) in verdicts.iter_mut().zip(er.answ()) {
+                if a.is_none() {
+                    *v = Verdict::Unknown;
+                }
+            }
+        }
+        self.failed += verdicts.iter().filter(|v| matches!(v, Verdict::Fail(_))).count();
+        // compare against the timing baseline, and store a new one if requested
+        let timings = Timings::new(&er, bench.as_ref());
+        let baseline_key = Baselines::key(d.dir, &job.input);
+        // with only one part run, the timings cannot be compared
+        let baseline = self.baselines.get(&baseline_key).copied().filter(|_| args.part.is_none());
+        let regressions = baseline.map(|b| timings.regressions(&b, args.threshold)).unwrap_or_default();
+        if (args.save_baseline || args.overwrite_baseline) && timeout.is_none() {
+            self.baselines_changed |= self.baselines.set(&baseline_key, timings, args.overwrite_baseline);
+        }
+        if f_raw {
+            er.print_raw_verified(&verdicts);
+            if let Some(e) = &timeout {
+                println!("day{}: {e}, stopped with partial results", day_number(d.dir));
+            }
+            if let Some(b) = &bench {
+                b.print_raw();
+            }
+            for (i, (stage, base)) in baseline.unwrap_or_default().stages().into_iter().enumerate() {
+                if let (Some(slower), Some(base)) = (regressions[i], base) {
+                    println!("Slower than baseline: {stage} +{slower:.0}% (baseline {})", duration_format(&Duration::from_nanos(base)));
+                }
+            }
+        }
+        if self.format == Format::Table {
+            let label = self.label(job);
+            let mut row = vec![label.clone()];
+            let mut answers: Vec<String> = er.answers().iter().zip(&verdicts).enumerate().map(|(i, (x, v))| {
+                match (x, v) {
+                    (None, _) if timeout.is_some() && er.wants_part(i + 1) => String::from("TIMEOUT"),
+                    (None, Verdict::Unknown) => String::from(""),
+                    (None, v) => format!("[{v}]"),
+                    (Some(a), v) if a.is_block() => {
+                        self.blocks.push((format!("{label} part{}", i + 1), a.to_string()));
+                        format!("(below) [{v}]")
+                    },
+                    (Some(a), v) => format!("{a} [{v}]"),
+                }
+            }).collect();
             row.append(&mut answers);
-            let mut times: Vec<String> = [er.parsetime(), er.time1(), er.time2(), er.cleanuptime()].iter()
+            // when benchmarking, show the median times
+            let mut shown = [er.parsetime(), er.time1(), er.time2(), er.cleanuptime()];
+            if bench.is_some() {
+                for (i, (_, t)) in timings.stages()[..3].iter().enumerate() {
+                    shown[i] = t.map(Duration::from_nanos);
+                }
+            }
+            let mut times: Vec<String> = shown.iter()
                 .map(|x| if let Some(d) = x { duration_format(d) } else { String::from("") }).collect();
+            // flag parse/time1/time2 if they are slower than the baseline
+            for (i, slower) in regressions[..3].iter().enumerate() {
+                if let Some(slower) = slower {
+                    times[i] += &format!(" (+{slower:.0}%)");
+                }
+            }
+            // and show what they allocated, when counting allocations
+            for (i, a) in er.allocs().iter().enumerate() {
+                if let (Some(a), false) = (a, times[i].is_empty()) {
+                    times[i] += &format!("\n{} in {}\npeak {}", bytes_format(a.bytes), a.count, bytes_format(a.peak));
+                }
+            }
             row.append(&mut times);
-            table.add_row(row);
+            if !er.metrics().is_empty() {
+                self.has_metrics = true;
+                row.push(er.metrics().iter().map(|(n, m)| format!("{n}: {m}")).collect::<Vec<_>>().join("\n"));
+            }
+            self.table.add_row(row);
+            if let Some(b) = &bench {
+                add_bench_rows(&mut self.bench_table, &label, b);
+            }
+        }
+        if self.keep_reports() {
+            let report = DayReport::new(d.dir, &job.input, &er, &verdicts, bench.as_ref());
+            self.reports.push(match &timeout {
+                Some(e) => report.with_error(e),
+                None => report,
+            });
         }
     }
-    if f_table {
-        println!("{table}");
+
+    // name of the puzzle in the table, with the input if there can be more than one
+    fn label(&self, job: &Job) -> String {
+        match self.multiple_inputs {
+            true => format!("{} {}", job.day.dir, job.input),
+            false => job.day.dir.to_string(),
+        }
     }
-    if days.len() > 1 {
-        if f_raw {
-            println!("===");
+
+    fn keep_reports(&self) -> bool {
+        self.format == Format::Json || self.format == Format::Csv || self.args.report_file.is_some()
+    }
+
+    // add a puzzle that gave an error, panicked, timed out or could not read its input
+    fn add_error(&mut self, job: &Job, e: RunError) {
+        self.failed_puzzles += 1;
+        let dir = job.day.dir;
+        match self.format {
+            Format::Raw => println!("day{}: {e}", day_number(dir)),
+            Format::Table => {
+                let msg = match e {
+                    RunError::Puzzle(_) => format!("ERROR: {e}"),
+                    _ => e.to_string(),
+                };
+                self.table.add_row(vec![self.label(job), msg]);
+            },
+            Format::Json | Format::Csv => (),
+        }
+        if self.keep_reports() {
+            self.reports.push(DayReport::error(dir, &job.input, &e));
         }
-        println!("Total puzzles runtime: {:?}", total_time);
     }
-}
 
-// download input to puzzle
-fn download_input(rootdir: &PathBuf, dirname: &str, target: &PathBuf, year: u16) {
-    let session_cookie = match get_session_cookie(rootdir) {
-        Err(e) => panic!("No input file, and no session cookie found: {e}"),
-        Ok(s) => format!("session={s}"),
-    };
-    let daynum = first_number(dirname);
-    let url = format!("https://adventofcode.com/{year}/day/{daynum}/input");
-    let client = reqwest::blocking::Client::new();
-    let res = client.get(&url)
-        .header(reqwest::header::COOKIE, session_cookie)
-        .send();
-    let mut resp = match res {
-        Err(e) => panic!("Cannot download input from {url}: {e}"),
-        Ok(resp) if !resp.status().is_success() => panic!("Error downloading input from {url}: {}", resp.status()),
-        Ok(resp) => resp,
-    };
-    // make sure output directory exists. Create it if not
-    let targetdir = target.parent().unwrap();
-    let meta = fs::metadata(targetdir);
-    if meta.is_err() && meta.err().unwrap().kind() == ErrorKind::NotFound {
-        println!("Creating input directory {}", targetdir.to_string_lossy());
-        fs::create_dir(targetdir).expect("Cannot create input directory");
+    // output everything that is collected. Returns false if any of the answers failed verification,
+    // or any of the puzzles failed to run.
+    fn finish(self, ndays: usize, wallclock: Duration, workers: usize) -> bool {
+        let format = self.format;
+        if format == Format::Table {
+            let mut table = self.table;
+            if self.has_metrics {
+                table.set_header([&TABLE_HEADER[..], &["metrics"]].concat());
+            }
+            println!("{table}");
+            for (name, block) in &self.blocks {
+                println!("{name}:\n{block}");
+            }
+            if let Some(runs) = self.args.bench {
+                println!("Benchmark over {runs} runs, after {} warm-up runs:", self.args.warmup);
+                println!("{}", self.bench_table);
+            }
+        }
+        let written = match format {
+            Format::Json => write_json(io::stdout().lock(), &self.reports),
+            Format::Csv => write_csv(io::stdout().lock(), &self.reports),
+            _ => Ok(()),
+        };
+        if let Err(e) = written {
+            eprintln!("Error writing output: {e}");
+        }
+        if let Some(path) = &self.args.report_file {
+            if let Err(e) = File::create(path).and_then(|fh| write_json(fh, &self.reports)) {
+                eprintln!("Error writing report to {}: {e}", path.to_string_lossy());
+            }
+        }
+        if self.baselines_changed {
+            let path = self.baselines.path().to_string_lossy();
+            match self.baselines.save() {
+                Ok(_) => eprintln!("Saved timing baselines to {path}"),
+                Err(e) => eprintln!("Error saving timing baselines to {path}: {e}"),
+            }
+        }
+        if ndays > 1 && (format == Format::Raw || format == Format::Table) {
+            if format == Format::Raw {
+                println!("===");
+            }
+            println!("Total puzzles runtime: {:?}", self.total_time);
+            if workers > 1 {
+                println!("Wall clock time: {wallclock:?}, using {workers} jobs");
+            }
+        }
+        if self.failed > 0 {
+            eprintln!("{} answer(s) failed verification", self.failed);
+        }
+        if self.failed_puzzles > 0 {
+            eprintln!("{} puzzle(s) failed or timed out", self.failed_puzzles);
+        }
+        self.failed == 0 && self.failed_puzzles == 0
     }
-    let mut fh = match File::options().write(true).create_new(true).open(target) {
-        Err(e) => panic!("Cannot create {}: {e}", target.to_string_lossy()),
-        Ok(f) => f,
-    };
-    println!("Downloading input from {url}");
-    resp.copy_to(&mut fh).expect("Error reading from URL writing to example input");
 }
 
-fn get_session_cookie(rootdir: &PathBuf) -> std::io::Result<String> {
-    match env::var("SESSION_COOKIE") {
-        Ok(s) => return Ok(s),
-        _ => (),
-    };
-    let mut cookiefile = rootdir.clone();
-    cookiefile.push("session.cookie");
-    let mut fh = File::open(cookiefile)?;
-    let mut contents = String::new();
-    fh.read_to_string(&mut contents)?;
-    Ok(contents.trim().to_string())
+fn add_bench_rows(table: &mut Table, dir: &str, b: &BenchStats) {
+    // only show the puzzle name on the first row
+    let mut name = dir;
+    for (stage, stats) in b.stages() {
+        if let Some(s) = stats {
+            let mut row = vec![name.to_string(), stage.to_string()];
+            row.extend([s.min, s.median, s.mean, s.stddev, s.p95].iter().map(duration_format));
+            table.add_row(row);
+            name = "";
+        }
+    }
 }
 
 // libc-specific: get access to uid
@@ -236,7 +780,7 @@ pub fn find_root_dir(target: &str) -> std::io::Result<PathBuf> {
         find_in_ancestors(env::current_dir()?, target, uid, &mut seen).or_else(|_|
             // search again, from program
             find_in_ancestors(PathBuf::from(env::args().next().unwrap()).canonicalize()?, target, uid, &mut seen))?;
-    return Ok(root_dir);
+    Ok(root_dir)
 }
 
 // find a subdirectory somewhere in the current dir or one of the directories above, only checking directories owned by the given uid.
@@ -277,10 +821,37 @@ fn find_in_ancestors(startdir: PathBuf, target: &str, uid: u32, seen: &mut HashM
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("10"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_timeout("0.5"), Ok(Duration::from_millis(500)));
+        assert!(parse_timeout("0").is_err());
+        assert!(parse_timeout("soon").is_err());
+    }
+
+    fn do_nothing(_i: Input, _er: &mut ExRunner) -> PuzzleResult {
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_year() {
+        static YEARS: &[Year] = &[
+            Year { year: 2022, days: &[Day { dir: "2022/day1_calories", solve: do_nothing }] },
+            Year { year: 2023, days: &[Day { dir: "day1_trebuchet", solve: do_nothing }] },
+        ];
+        let root = Path::new("/nonexistent");
+        assert_eq!(select_year(YEARS, None, 2023, root).unwrap().year, 2023);
+        let y = select_year(YEARS, Some(2022), 2023, root).unwrap();
+        assert_eq!(y.days[0].number(), 1);
+        assert_eq!(select_year(YEARS, Some(2015), 2023, root).err(), Some("no puzzles for 2015, only for 2022, 2023".to_string()));
+    }
+
     #[test]
     fn test_first_number() {
         assert_eq!(first_number("foo42bar"), "42");
         assert_eq!(first_number("123"), "123");
         assert_eq!(first_number("yolo"), "");
+        assert_eq!(day_number("2022/day7_no_space"), "7");
+        assert_eq!(day_number("day12_hot_springs"), "12");
     }
 }
@@ -3,27 +3,15 @@ use std::process::exit;
 use std::io::ErrorKind;
 use aoc::*;
 
-const YEAR: u16 = 2023;
+// all puzzle days by year, generated by build.rs from the day* directories of the workspace. The puzzle number is
+// the first number in the directory name. A day is only found if it is also a dependency in Cargo.toml: `aoc new`
+// adds it, otherwise it has to be added by hand, and the build fails until it is.
+include!(concat!(env!("OUT_DIR"), "/days.rs"));
 
-// all puzzle days. Note that the puzzle number should be the first number in the directory name.
-const DAYS: &'static [Day] = &[
-    Day{ dir: "day1_trebuchet", solve: day1_trebuchet::solve },
-    Day{ dir: "day2_cube_conundrum", solve: day2_cube_conundrum::solve },
-    Day{ dir: "day3_gear_ratios", solve: day3_gear_ratios::solve },
-    Day{ dir: "day4_scratchcards", solve: day4_scratchcards::solve },
-    Day{ dir: "day5_seed_fertilizer", solve: day5_seed_fertilizer::solve },
-    Day{ dir: "day6_wait_for_it", solve: day6_wait_for_it::solve },
-    Day{ dir: "day7_camel_cards", solve: day7_camel_cards::solve },
-    Day{ dir: "day8_haunted_wasteland", solve: day8_haunted_wasteland::solve },
-    Day{ dir: "day9_mirage_maintenance", solve: day9_mirage_maintenance::solve },
-    Day{ dir: "day10_pipe_maze", solve: day10_pipe_maze::solve },
-    Day{ dir: "day11_cosmic_expansion", solve: day11_cosmic_expansion::solve },
-    Day{ dir: "day12_hot_springs", solve: day12_hot_springs::solve },
-    Day{ dir: "day13_point_of_incidence", solve: day13_point_of_incidence::solve },
-    Day{ dir: "day14_parabolic_dish", solve: day14_parabolic_dish::solve },
-    Day{ dir: "day15_lens_library", solve: day15_lens_library::solve },
-    Day{ dir: "day16_floor_will_be_lava", solve: day16_floor_will_be_lava::solve },
-];
+// build with --features count-allocs to see the memory allocated in every stage of the puzzles
+#[cfg(feature = "count-allocs")]
+#[global_allocator]
+static ALLOC: exrunner::CountingAlloc = exrunner::CountingAlloc;
 
 fn main() {
     let args = CliArgs::parse();
@@ -34,26 +22,99 @@ fn main() {
             "Cannot use --all and explicit puzzle numbers.")
             .exit();
     }
-    let rootdir = find_root_dir(&DAYS[0].dir);
+    let default_days = YEARS.iter().find(|y| y.year == DEFAULT_YEAR).unwrap().days;
+    let rootdir = find_root_dir(default_days[0].dir);
     if let Err(e) = rootdir {
         eprintln!("Cannot find path to exercises: {:?}", e);
         exit(2);
     }
     let rootdir = rootdir.unwrap();
+    if let Some(Command::New { day, name }) = &args.command {
+        // a new day may be the first of its year, so the year does not need to exist yet
+        let year = args.year.filter(|&y| y != DEFAULT_YEAR);
+        match new_day(&rootdir, year, *day, name) {
+            Ok(dir) => println!("Created {}", dir.to_string_lossy()),
+            Err(e) => {
+                eprintln!("Cannot create puzzle {day}: {e}");
+                exit(1);
+            },
+        }
+        return;
+    }
+    let year = select_year(YEARS, args.year, DEFAULT_YEAR, &rootdir).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        exit(1);
+    });
+    let days = year.days;
+    if let Some(Command::FetchExample { day }) = &args.command {
+        let d = find_day(days, *day);
+        let fetched = InputProvider::new(&rootdir, year.year)
+            .and_then(|p| fetch_examples(&p, *day, &rootdir.join(d.dir).join("input")));
+        match fetched {
+            Ok(files) => files.iter().for_each(|f| println!("Wrote {}", f.to_string_lossy())),
+            Err(e) => {
+                eprintln!("Cannot fetch examples for puzzle {day}: {e}");
+                exit(1);
+            },
+        }
+        return;
+    }
+    if let Some(Command::Submit { day, part }) = &args.command {
+        match submit_answer(&rootdir, find_day(days, *day), *part, year.year) {
+            Ok(outcome) => println!("{outcome}"),
+            Err(e) => {
+                eprintln!("Cannot submit: {e}");
+                exit(1);
+            },
+        }
+        return;
+    }
+    if args.watch {
+        if args.input.as_deref() == Some(STDIN) {
+            eprintln!("Cannot watch input from stdin");
+            exit(1);
+        }
+        let day = match args.puzzle.as_slice() {
+            [] => current_puzzle(days).map(|d| &d[0]).unwrap_or_else(|_| {
+                eprintln!("Not in a puzzle directory, which puzzle should be watched?");
+                exit(1);
+            }),
+            [p] => find_day(days, *p),
+            _ => {
+                eprintln!("Can only watch one puzzle");
+                exit(1);
+            },
+        };
+        if let Err(e) = watch(&rootdir, day, year.year, &args) {
+            eprintln!("Cannot watch {}: {e}", day.dir);
+            exit(1);
+        }
+        return;
+    }
     // which puzzles to run
-    if args.all {
-        run_puzzles(rootdir, &args, &DAYS, YEAR);
+    let all_ok = if args.all {
+        run_puzzles(rootdir, &args, days, year.year)
     } else if !args.puzzle.is_empty() {
-        run_puzzles(rootdir, &args, &to_days(&args.puzzle, &DAYS), YEAR);
+        run_puzzles(rootdir, &args, &to_days(&args.puzzle, days), year.year)
     } else {
-        let puzzle = current_puzzle(&DAYS);
+        let puzzle = current_puzzle(days);
         match puzzle {
-            Ok(d) => run_puzzles(rootdir, &args, d, YEAR),
-            Err(e) if e.kind() == ErrorKind::NotFound => run_puzzles(rootdir, &args, &DAYS[DAYS.len()-1..], YEAR),
+            Ok(d) => run_puzzles(rootdir, &args, d, year.year),
+            Err(e) if e.kind() == ErrorKind::NotFound => run_puzzles(rootdir, &args, &days[days.len()-1..], year.year),
             Err(e) => {
                 eprintln!("Error searching for puzzle from current dir: {e}");
                 exit(1);
             },
-        };
+        }
+    };
+    if !all_ok {
+        exit(1);
     }
 }
+
+fn find_day(days: &'static [Day], day: u32) -> &'static Day {
+    days.iter().find(|d| d.number() == day).unwrap_or_else(|| {
+        eprintln!("Puzzle number {day} does not exist");
+        exit(1);
+    })
+}
@@ -0,0 +1,63 @@
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
+use exrunner::{Progress, ProgressSink, duration_format};
+
+// on a terminal the status line is updated often, in a log only now and then
+const TTY_INTERVAL: Duration = Duration::from_millis(100);
+const LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+// one line describing the progress of a puzzle
+pub fn describe(name: &str, p: &Progress) -> String {
+    let mut line = format!("{name}: {} {}", p.phase, p.pos);
+    if let Some(total) = p.total {
+        line += &format!("/{total} ({:.0}%)", p.pos as f64 * 100.0 / total.max(1) as f64);
+    }
+    line += &format!(", {}", duration_format(&p.elapsed));
+    if let Some(eta) = p.eta() {
+        line += &format!(", {} left", duration_format(&eta));
+    }
+    if p.done {
+        line += ", done";
+    }
+    line
+}
+
+// Show the progress of a puzzle on stderr: as a status line that is updated in place on a terminal,
+// or else as log lines. Returns the sink, and how often it wants to be called.
+pub fn stderr_progress(name: &str) -> (ProgressSink<'static>, Duration) {
+    let name = name.to_string();
+    if io::stderr().is_terminal() {
+        let sink = move |p: &Progress| {
+            let mut err = io::stderr().lock();
+            // when done, clear the status line for the output that follows
+            let _ = match p.done {
+                true => write!(err, "\r\x1b[K"),
+                false => write!(err, "\r{}\x1b[K", describe(&name, p)),
+            };
+            let _ = err.flush();
+        };
+        (Box::new(sink), TTY_INTERVAL)
+    } else {
+        let sink = move |p: &Progress| {
+            // phases that ended before anything was logged are not worth a line
+            if !p.done || p.elapsed >= LOG_INTERVAL {
+                eprintln!("{}", describe(&name, p));
+            }
+        };
+        (Box::new(sink), LOG_INTERVAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let mut p = Progress { phase: "seeds".to_string(), pos: 25, total: Some(100), elapsed: Duration::from_secs(1), done: false };
+        assert_eq!(describe("day5", &p), "day5: seeds 25/100 (25%), 1s, 3s left");
+        p.total = None;
+        p.done = true;
+        assert_eq!(describe("day5", &p), "day5: seeds 25, 1s, done");
+    }
+}
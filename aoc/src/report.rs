@@ -0,0 +1,366 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::Duration;
+use serde::Serialize;
+use exrunner::{ExRunner, Verdict, BenchStats, Stats, Metric, Answer};
+use crate::runner::RunError;
+
+// Machine readable result of running a single day, written out as JSON or CSV.
+// All durations are in nanoseconds.
+#[derive(Serialize, Debug)]
+pub struct DayReport {
+    pub day: String,
+    pub input: String,
+    // "ok", or why the puzzle did not run to completion: "invalid" for bad input, "failed", "timeout" or "error"
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub answers: Vec<Option<AnswerReport>>,
+    // "int", "text" or "block" for each answer, so a block can be told apart from other text
+    pub answer_kinds: Vec<Option<&'static str>>,
+    pub labels: Vec<Option<String>>,
+    pub verdicts: Vec<&'static str>,
+    pub parse_ns: Option<u64>,
+    pub time1_ns: Option<u64>,
+    pub time2_ns: Option<u64>,
+    pub cleanup_ns: Option<u64>,
+    pub total_ns: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bench: Option<BenchReport>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, MetricReport>,
+}
+
+// An answer: a number for integer answers, otherwise a string, with lines separated by newlines
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum AnswerReport {
+    Int(i128),
+    Text(String),
+}
+
+impl AnswerReport {
+    fn new(a: &Answer) -> AnswerReport {
+        match a {
+            Answer::Int(n) => AnswerReport::Int(*n),
+            a => AnswerReport::Text(a.to_string()),
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            AnswerReport::Int(n) => n.to_string(),
+            AnswerReport::Text(s) => s.clone(),
+        }
+    }
+}
+
+// A metric reported by the puzzle: a number for counters and gauges, or a summary for histograms
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum MetricReport {
+    Counter(u64),
+    Gauge(i64),
+    Histogram { count: u64, sum: u64, min: u64, max: u64, mean: f64 },
+}
+
+impl MetricReport {
+    fn new(m: &Metric) -> MetricReport {
+        match *m {
+            Metric::Counter(n) => MetricReport::Counter(n),
+            Metric::Gauge(v) => MetricReport::Gauge(v),
+            Metric::Histogram(h) => MetricReport::Histogram { count: h.count, sum: h.sum, min: h.min, max: h.max, mean: h.mean() },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct BenchReport {
+    pub warmup: usize,
+    pub runs: usize,
+    pub parse: Option<StatsReport>,
+    pub part1: Option<StatsReport>,
+    pub part2: Option<StatsReport>,
+    pub total: Option<StatsReport>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StatsReport {
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub mean_ns: u64,
+    pub stddev_ns: u64,
+    pub p95_ns: u64,
+}
+
+impl StatsReport {
+    fn new(s: Stats) -> StatsReport {
+        let ns = |d: Duration| d.as_nanos() as u64;
+        StatsReport { min_ns: ns(s.min), median_ns: ns(s.median), mean_ns: ns(s.mean), stddev_ns: ns(s.stddev), p95_ns: ns(s.p95) }
+    }
+
+    fn values(s: Option<&StatsReport>) -> [Option<u64>; 5] {
+        match s {
+            None => [None; 5],
+            Some(s) => [s.min_ns, s.median_ns, s.mean_ns, s.stddev_ns, s.p95_ns].map(Some),
+        }
+    }
+}
+
+impl BenchReport {
+    fn new(b: &BenchStats) -> BenchReport {
+        BenchReport {
+            warmup: b.warmup,
+            runs: b.runs,
+            parse: b.parse.map(StatsReport::new),
+            part1: b.part1.map(StatsReport::new),
+            part2: b.part2.map(StatsReport::new),
+            total: b.total.map(StatsReport::new),
+        }
+    }
+
+    fn csv_header() -> Vec<String> {
+        let mut header = vec!["runs".to_string()];
+        for stage in ["parse", "part1", "part2", "total"] {
+            for stat in ["min", "median", "mean", "stddev", "p95"] {
+                header.push(format!("{stage}_{stat}_ns"));
+            }
+        }
+        header
+    }
+
+    fn csv_record(b: Option<&BenchReport>) -> Vec<String> {
+        let mut rec = vec![b.map(|b| b.runs.to_string()).unwrap_or_default()];
+        let stages = b.map(|b| [b.parse.as_ref(), b.part1.as_ref(), b.part2.as_ref(), b.total.as_ref()]).unwrap_or_default();
+        for s in stages {
+            rec.extend(StatsReport::values(s).map(|n| n.map(|n| n.to_string()).unwrap_or_default()));
+        }
+        rec
+    }
+}
+
+fn nanos(d: Option<Duration>) -> Option<u64> {
+    d.map(|d| d.as_nanos() as u64)
+}
+
+impl DayReport {
+    pub fn new(day: &str, input: &str, er: &ExRunner, verdicts: &[Verdict], bench: Option<&BenchStats>) -> DayReport {
+        let answers: Vec<_> = er.answers().iter().map(|a| a.as_ref().map(AnswerReport::new)).collect();
+        let answer_kinds = er.answers().iter().map(|a| a.as_ref().map(Answer::kind)).collect();
+        let labels = er.labels().iter().zip(&answers)
+            .map(|(l, a)| a.as_ref().map(|_| l.to_string()))
+            .collect();
+        DayReport {
+            day: day.to_string(),
+            input: input.to_string(),
+            status: "ok",
+            error: None,
+            answers,
+            answer_kinds,
+            labels,
+            verdicts: verdicts.iter().map(|v| v.status()).collect(),
+            parse_ns: nanos(er.parsetime()),
+            time1_ns: nanos(er.time1()),
+            time2_ns: nanos(er.time2()),
+            cleanup_ns: nanos(er.cleanuptime()),
+            total_ns: nanos(er.totaltime()),
+            bench: bench.map(BenchReport::new),
+            metrics: er.metrics().iter().map(|(n, m)| (n.to_string(), MetricReport::new(m))).collect(),
+        }
+    }
+
+    // report of a puzzle that stopped early, but still has results, like at a timeout
+    pub fn with_error(mut self, e: &RunError) -> DayReport {
+        self.status = e.status();
+        self.error = Some(e.to_string());
+        self
+    }
+
+    // report of a puzzle that did not run to completion
+    pub fn error(day: &str, input: &str, e: &RunError) -> DayReport {
+        DayReport {
+            day: day.to_string(),
+            input: input.to_string(),
+            status: e.status(),
+            error: Some(e.to_string()),
+            answers: vec![None, None],
+            answer_kinds: vec![None, None],
+            labels: vec![None, None],
+            verdicts: vec!["unknown", "unknown"],
+            parse_ns: None,
+            time1_ns: None,
+            time2_ns: None,
+            cleanup_ns: None,
+            total_ns: None,
+            bench: None,
+            metrics: BTreeMap::new(),
+        }
+    }
+
+    fn csv_header() -> Vec<&'static str> {
+        vec!["day", "input", "part1", "part2", "label1", "label2", "verdict1", "verdict2",
+            "parse_ns", "time1_ns", "time2_ns", "cleanup_ns", "total_ns", "status", "error"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        let opt = |o: &Option<String>| o.clone().unwrap_or_default();
+        let num = |n: Option<u64>| n.map(|n| n.to_string()).unwrap_or_default();
+        let mut rec = vec![self.day.clone(), self.input.clone()];
+        for i in 0..2 {
+            rec.push(self.answers.get(i).cloned().flatten().map(|a| a.text()).unwrap_or_default());
+        }
+        for i in 0..2 {
+            rec.push(self.labels.get(i).map(opt).unwrap_or_default());
+        }
+        for i in 0..2 {
+            rec.push(self.verdicts.get(i).unwrap_or(&"unknown").to_string());
+        }
+        for n in [self.parse_ns, self.time1_ns, self.time2_ns, self.cleanup_ns, self.total_ns] {
+            rec.push(num(n));
+        }
+        rec.push(self.status.to_string());
+        rec.push(opt(&self.error));
+        rec
+    }
+}
+
+// write reports as a JSON array
+pub fn write_json(out: impl Write, reports: &[DayReport]) -> io::Result<()> {
+    let mut out = out;
+    serde_json::to_writer_pretty(&mut out, reports)?;
+    writeln!(out)
+}
+
+// write reports as CSV, with a header line. Benchmark columns are added if any of the reports has them.
+pub fn write_csv(out: impl Write, reports: &[DayReport]) -> io::Result<()> {
+    let with_bench = reports.iter().any(|r| r.bench.is_some());
+    let mut wr = csv::Writer::from_writer(out);
+    let mut header: Vec<String> = DayReport::csv_header().into_iter().map(String::from).collect();
+    if with_bench {
+        header.append(&mut BenchReport::csv_header());
+    }
+    wr.write_record(header)?;
+    for r in reports {
+        let mut rec = r.csv_record();
+        if with_bench {
+            rec.append(&mut BenchReport::csv_record(r.bench.as_ref()));
+        }
+        wr.write_record(rec)?;
+    }
+    wr.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    fn report() -> DayReport {
+        let er = ExRunner::run("test".to_string(), |_i, r| {
+            r.parse_done();
+            r.part1("multi\nline, \"quoted\"", Some("Label one"));
+            r.count("states", 5);
+            r.observe("depth", 2);
+            Ok(())
+        }, BufReader::new("".as_bytes()));
+        let verdicts = er.verify(&[Some("nope".to_string())]);
+        DayReport::new("day0_test", "input.txt", &er, &verdicts, None)
+    }
+
+    fn int_report() -> DayReport {
+        let er = ExRunner::run("test".to_string(), |_i, r| {
+            r.part1(-7, None);
+            r.part2(1u64 << 40, None);
+            Ok(())
+        }, BufReader::new("".as_bytes()));
+        let verdicts = er.verify(&[Some("-7".to_string()), Some(" 1099511627776".to_string())]);
+        DayReport::new("day0_int", "input.txt", &er, &verdicts, None)
+    }
+
+    #[test]
+    fn test_json() {
+        let mut out = Vec::new();
+        write_json(&mut out, &[report()]).unwrap();
+        let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(v[0]["day"], "day0_test");
+        assert_eq!(v[0]["input"], "input.txt");
+        assert_eq!(v[0]["status"], "ok");
+        assert!(v[0].get("error").is_none());
+        assert_eq!(v[0]["answers"][0], "multi\nline, \"quoted\"");
+        assert!(v[0]["answers"][1].is_null());
+        assert_eq!(v[0]["answer_kinds"], serde_json::json!(["block", null]));
+        assert_eq!(v[0]["labels"][0], "Label one");
+        assert_eq!(v[0]["verdicts"], serde_json::json!(["fail", "unknown"]));
+        assert!(v[0]["parse_ns"].is_u64());
+        assert!(v[0]["time2_ns"].is_null());
+        assert_eq!(v[0]["metrics"]["states"], 5);
+        assert_eq!(v[0]["metrics"]["depth"]["max"], 2);
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut out = Vec::new();
+        write_csv(&mut out, &[report()]).unwrap();
+        let mut rd = csv::Reader::from_reader(&out[..]);
+        assert_eq!(rd.headers().unwrap().len(), 15);
+        let rec = rd.records().next().unwrap().unwrap();
+        assert_eq!(&rec[0], "day0_test");
+        assert_eq!(&rec[1], "input.txt");
+        assert_eq!(&rec[2], "multi\nline, \"quoted\"");
+        assert_eq!(&rec[3], "");
+        assert_eq!(&rec[6], "fail");
+        assert!(rec[8].parse::<u64>().is_ok());
+        assert_eq!(&rec[13], "ok");
+        assert_eq!(&rec[14], "");
+    }
+
+    #[test]
+    fn test_int_answers() {
+        let mut out = Vec::new();
+        write_json(&mut out, &[int_report()]).unwrap();
+        let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        // integer answers are numbers in JSON
+        assert_eq!(v[0]["answers"], serde_json::json!([-7, 1099511627776u64]));
+        assert_eq!(v[0]["answer_kinds"], serde_json::json!(["int", "int"]));
+        assert_eq!(v[0]["labels"], serde_json::json!(["part1", "part2"]));
+        assert_eq!(v[0]["verdicts"], serde_json::json!(["pass", "pass"]));
+        let mut out = Vec::new();
+        write_csv(&mut out, &[int_report()]).unwrap();
+        let mut rd = csv::Reader::from_reader(&out[..]);
+        let rec = rd.records().next().unwrap().unwrap();
+        assert_eq!((&rec[2], &rec[3]), ("-7", "1099511627776"));
+    }
+
+    #[test]
+    fn test_error() {
+        let reports = [DayReport::error("day0_slow", "input.txt", &RunError::Timeout(std::time::Duration::from_secs(2)))];
+        let mut out = Vec::new();
+        write_json(&mut out, &reports).unwrap();
+        let v: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(v[0]["status"], "timeout");
+        assert_eq!(v[0]["error"], "TIMEOUT after 2s");
+        assert!(v[0]["total_ns"].is_null());
+        let mut out = Vec::new();
+        write_csv(&mut out, &reports).unwrap();
+        let mut rd = csv::Reader::from_reader(&out[..]);
+        let rec = rd.records().next().unwrap().unwrap();
+        assert_eq!((&rec[13], &rec[14]), ("timeout", "TIMEOUT after 2s"));
+    }
+
+    #[test]
+    fn test_bench_csv() {
+        let (er, stats) = exrunner::bench("bench".to_string(), |_i, r| { r.part1(1, None); Ok(()) },
+            || BufReader::new("".as_bytes()), None, 0, 3);
+        let reports = [report(), DayReport::new("day1_bench", "input.txt", &er, &[], Some(&stats))];
+        let mut out = Vec::new();
+        write_csv(&mut out, &reports).unwrap();
+        let mut rd = csv::Reader::from_reader(&out[..]);
+        assert_eq!(rd.headers().unwrap().len(), 15 + 21);
+        assert_eq!(&rd.headers().unwrap()[16], "parse_min_ns");
+        let recs: Vec<_> = rd.records().map(|r| r.unwrap()).collect();
+        assert_eq!(&recs[0][15], "");
+        assert_eq!(&recs[1][15], "3");
+        assert_eq!(&recs[1][16], "");
+        assert!(recs[1][21].parse::<u64>().is_ok());
+    }
+}
@@ -0,0 +1,327 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write, BufReader, Cursor};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Once, mpsc::{self, RecvTimeoutError}};
+use std::thread;
+use std::time::Duration;
+use exrunner::{ExRunner, ExCtx, BenchStats, PuzzleError, Level, CancelToken};
+use crate::{Day, Input};
+use crate::progress::stderr_progress;
+
+// threads running puzzles get a big stack, like the main thread has, as some puzzles recurse deeply
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+// how long a puzzle gets to stop after the timeout, before it is abandoned
+const CANCEL_GRACE: Duration = Duration::from_secs(1);
+
+// How to run a puzzle
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RunOpts {
+    pub bench: Option<u32>,
+    pub warmup: u32,
+    pub timeout: Option<Duration>,
+    // only solve this part
+    pub part: Option<usize>,
+    // debug output up to this level
+    pub level: Level,
+    // show the progress of long running puzzles
+    pub progress: bool,
+}
+
+// Where the input of a puzzle comes from: a file, or data that was already read, like from stdin
+#[derive(Clone, Debug)]
+pub enum InputSource {
+    File(PathBuf),
+    Data(Arc<[u8]>),
+}
+
+impl InputSource {
+    fn open(&self) -> io::Result<Input> {
+        match self {
+            InputSource::File(fname) => Ok(Box::new(BufReader::new(File::open(fname)?))),
+            InputSource::Data(data) => Ok(Box::new(Cursor::new(data.clone()))),
+        }
+    }
+
+    // all of the input in memory
+    fn read_all(&self) -> io::Result<Arc<[u8]>> {
+        match self {
+            InputSource::File(fname) => {
+                let mut buf = Vec::new();
+                File::open(fname)?.read_to_end(&mut buf)?;
+                Ok(buf.into())
+            },
+            InputSource::Data(data) => Ok(data.clone()),
+        }
+    }
+}
+
+// Result of running a puzzle
+pub struct DayRun {
+    pub er: ExRunner<'static>,
+    pub bench: Option<BenchStats>,
+    // set if the puzzle stopped at the timeout. The answers it found until then are kept.
+    pub timeout: Option<Duration>,
+}
+
+// Why running a puzzle did not give a result
+#[derive(Debug)]
+pub enum RunError {
+    Io(io::Error),
+    Fetch(String),
+    Puzzle(PuzzleError),
+    Panic(String),
+    Timeout(Duration),
+}
+
+impl RunError {
+    pub fn status(&self) -> &'static str {
+        match self {
+            RunError::Io(_) | RunError::Fetch(_) => "error",
+            RunError::Puzzle(_) => "invalid",
+            RunError::Panic(_) => "failed",
+            RunError::Timeout(_) => "timeout",
+        }
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Io(e) => write!(f, "ERROR: cannot read input: {e}"),
+            RunError::Fetch(e) => write!(f, "ERROR: cannot fetch input: {e}"),
+            RunError::Puzzle(e) => write!(f, "{e}"),
+            RunError::Panic(msg) => write!(f, "FAILED: {msg}"),
+            RunError::Timeout(t) => write!(f, "TIMEOUT after {t:?}"),
+        }
+    }
+}
+
+// Debug output of a puzzle running in parallel. Buffered, so it can be shown in order.
+#[derive(Clone, Default)]
+pub struct DebugBuf(Arc<Mutex<Vec<u8>>>);
+
+impl DebugBuf {
+    // take the output written so far
+    pub fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Write for DebugBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+thread_local! {
+    // set in the threads running puzzles, so the panic hook knows to keep quiet
+    static IN_PUZZLE: Cell<bool> = const { Cell::new(false) };
+    // where the last panic in this thread happened
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+// A panic in a puzzle is reported as a failed puzzle, so the default hook should not print it.
+// Only keep the location of the panic, to add to the message.
+fn install_panic_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let prev = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if IN_PUZZLE.get() {
+                PANIC_LOCATION.set(info.location().map(|l| l.to_string()));
+            } else {
+                prev(info);
+            }
+        }));
+    });
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        String::from("unknown panic")
+    }
+}
+
+// run a single puzzle, or benchmark it. Debug output goes to the given writer, if any.
+// The puzzle can poll the cancel token to stop early, that is not done when benchmarking.
+fn run_day(day: &Day, source: &InputSource, opts: RunOpts, debug: Option<Box<dyn Write + Send>>, cancel: CancelToken) -> Result<DayRun, RunError> {
+    let mut run = if let Some(runs) = opts.bench {
+        // read the input in memory once, so file I/O does not influence the timings
+        let buf = source.read_all().map_err(RunError::Io)?;
+        let (er, stats) = exrunner::bench(day.dir.to_string(), day.solve,
+            || Box::new(Cursor::new(buf.clone())) as Input, opts.part, opts.warmup as usize, runs as usize);
+        DayRun { er, bench: Some(stats), timeout: None }
+    } else {
+        let mut ct = ExCtx::new(day.solve, source.open().map_err(RunError::Io)?);
+        ct.with_part(opts.part);
+        ct.with_level(opts.level);
+        ct.with_cancel(cancel);
+        if opts.progress {
+            let (sink, interval) = stderr_progress(day.dir);
+            ct.with_progress(sink, interval);
+        }
+        if let Some(wr) = debug {
+            ct.with_debugwrite(wr);
+        }
+        DayRun { er: ct.do_run(day.dir.to_string()), bench: None, timeout: None }
+    };
+    match run.er.take_error() {
+        // only the harness cancels, when the puzzle runs out of time
+        Some(e) if e.is_cancelled() => Ok(DayRun { timeout: opts.timeout, ..run }),
+        Some(e) => Err(RunError::Puzzle(e)),
+        None => Ok(run),
+    }
+}
+
+// Run a puzzle in its own thread, so a panic only fails this puzzle. If it takes longer than
+// the timeout, the puzzle is asked to stop, and gets a little time to return what it has found.
+// If it does not poll for that, it is abandoned: its thread keeps running in the background.
+pub fn run_isolated(day: &Day, source: &InputSource, opts: RunOpts, debug: Option<Box<dyn Write + Send>>) -> Result<DayRun, RunError> {
+    install_panic_hook();
+    let (tx, rx) = mpsc::channel();
+    let name = day.dir;
+    let (day, source) = (day.clone(), source.clone());
+    let cancel = CancelToken::new();
+    let puzzle_cancel = cancel.clone();
+    thread::Builder::new()
+        .name(format!("puzzle {}", day.dir))
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            IN_PUZZLE.set(true);
+            let result = match panic::catch_unwind(AssertUnwindSafe(|| run_day(&day, &source, opts, debug, puzzle_cancel))) {
+                Ok(run) => run,
+                Err(payload) => {
+                    let msg = panic_message(payload);
+                    Err(RunError::Panic(match PANIC_LOCATION.take() {
+                        Some(loc) => format!("{msg} (at {loc})"),
+                        None => msg,
+                    }))
+                },
+            };
+            // if the puzzle timed out, nobody is listening anymore
+            let _ = tx.send(result);
+        })
+        .map_err(RunError::Io)?;
+    let result = match opts.timeout {
+        None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        Some(t) => rx.recv_timeout(t).or_else(|e| match e {
+            RecvTimeoutError::Timeout => {
+                cancel.cancel();
+                rx.recv_timeout(CANCEL_GRACE)
+            },
+            e => Err(e),
+        }),
+    };
+    match result {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            // a thread cannot be killed, so a puzzle that ignores the cancel token keeps running
+            eprintln!("Warning: {name} did not stop at the timeout, it keeps running in the background \
+                and can slow down the puzzles after it");
+            Err(RunError::Timeout(opts.timeout.unwrap()))
+        },
+        Err(RecvTimeoutError::Disconnected) => Err(RunError::Panic(String::from("puzzle thread died"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use exrunner::PuzzleResult;
+
+    // any existing file will do as input
+    const INPUT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+
+    fn do_ok(_i: Input, er: &mut ExRunner) -> PuzzleResult {
+        er.part1(42, None);
+        Ok(())
+    }
+
+    fn do_panic(i: Input, _er: &mut ExRunner) -> PuzzleResult {
+        let first = i.lines().next().unwrap().unwrap();
+        first.parse::<u32>().expect("not a number");
+        Ok(())
+    }
+
+    fn do_error(_i: Input, _er: &mut ExRunner) -> PuzzleResult {
+        Err(PuzzleError::at_line(12, "invalid card 'X'"))
+    }
+
+    fn do_loop(_i: Input, _er: &mut ExRunner) -> PuzzleResult {
+        thread::sleep(Duration::from_secs(10));
+        Ok(())
+    }
+
+    fn do_poll(_i: Input, er: &mut ExRunner) -> PuzzleResult {
+        er.part1(1, None);
+        loop {
+            er.check_cancel()?;
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_run_isolated() {
+        let input = &InputSource::File(PathBuf::from(INPUT));
+        let day = Day { dir: "day0_ok", solve: do_ok };
+        let run = run_isolated(&day, input, RunOpts::default(), None).unwrap();
+        assert_eq!(run.er.answ()[0], Some("42".to_string()));
+
+        let day = Day { dir: "day0_panic", solve: do_panic };
+        match run_isolated(&day, input, RunOpts::default(), None) {
+            Err(e @ RunError::Panic(_)) => {
+                assert_eq!(e.status(), "failed");
+                let msg = e.to_string();
+                assert!(msg.starts_with("FAILED: not a number: "), "{msg}");
+                assert!(msg.contains("runner.rs"), "{msg}");
+            },
+            _ => panic!("expected a panic"),
+        }
+
+        let day = Day { dir: "day0_error", solve: do_error };
+        match run_isolated(&day, input, RunOpts::default(), None) {
+            Err(e @ RunError::Puzzle(_)) => assert_eq!(e.to_string(), "line 12: invalid card 'X'"),
+            _ => panic!("expected a puzzle error"),
+        }
+
+        let day = Day { dir: "day0_loop", solve: do_loop };
+        let opts = RunOpts { timeout: Some(Duration::from_millis(50)), ..Default::default() };
+        match run_isolated(&day, input, opts, None) {
+            Err(e @ RunError::Timeout(_)) => assert_eq!(e.to_string(), "TIMEOUT after 50ms"),
+            _ => panic!("expected a timeout"),
+        }
+
+        // a puzzle that polls for cancellation stops at the timeout, with what it found so far
+        let day = Day { dir: "day0_poll", solve: do_poll };
+        let run = run_isolated(&day, input, opts, None).unwrap();
+        assert_eq!(run.timeout, Some(Duration::from_millis(50)));
+        assert_eq!(run.er.answ(), vec![Some("1".to_string()), None]);
+
+        let day = Day { dir: "day0_ok", solve: do_ok };
+        match run_isolated(&day, &InputSource::File(PathBuf::from("/nonexistent/input.txt")), RunOpts::default(), None) {
+            Err(e @ RunError::Io(_)) => assert_eq!(e.status(), "error"),
+            _ => panic!("expected an I/O error"),
+        }
+
+        // input that is already in memory can be read more than once, also for benchmarks
+        let day = Day { dir: "day0_panic", solve: do_panic };
+        let data = InputSource::Data(b"12\n".to_vec().into());
+        assert!(run_isolated(&day, &data, RunOpts::default(), None).is_ok());
+        let opts = RunOpts { bench: Some(2), warmup: 1, ..Default::default() };
+        assert!(run_isolated(&day, &data, opts, None).unwrap().bench.is_some());
+    }
+}
@@ -0,0 +1,183 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use crate::first_number;
+
+// Directory (and crate) name for a puzzle: "day17_clumsy_crucible" for day 17, "Clumsy Crucible"
+pub fn day_dir_name(day: u32, name: &str) -> String {
+    let mut dir = format!("day{day}");
+    for word in name.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()) {
+        dir.push('_');
+        dir.push_str(&word.to_ascii_lowercase());
+    }
+    dir
+}
+
+fn cargo_toml(name: &str, exrunner: &str) -> String {
+    format!(r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[package.metadata.aoc]
+# the puzzle is solved by `pub fn solve` in src/lib.rs, or else name the Solver type that solves it:
+# solver = "MyPuzzle"
+
+# See more keys and their definitions at https://doc.rust-lang.org/cargo/reference/manifest.html
+
+[dependencies]
+exrunner = {{ path = "{exrunner}" }}
+"#)
+}
+
+fn lib_rs(day: u32) -> String {
+    format!(r#"use exrunner::{{ExRunner, PuzzleResult, numbered_lines}};
+use std::io::BufRead;
+
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {{
+    let mut lines = Vec::new();
+    for l in numbered_lines(input) {{
+        let (_lnum, line) = l?;
+        lines.push(line);
+    }}
+    er.parse_done();
+    er.part1(lines.len(), Some("Number of lines"));
+    Ok(())
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+    use std::io::BufReader;
+
+    fn test_input() -> BufReader<&'static [u8]> {{
+        BufReader::new(
+"
+".as_bytes()
+        )
+    }}
+
+    #[test]
+    fn test_part1() {{
+        let er = ExRunner::run("day {day}".to_string(), solve, test_input());
+        er.print_raw();
+        assert_eq!(er.answ()[0], Some("1".to_string()));
+    }}
+}}
+"#)
+}
+
+// add the new day as dependency of the aoc crate, in order of puzzle number
+fn add_dependency(manifest: &str, name: &str, dir: &str, day: u32) -> String {
+    let dep = format!("{name} = {{ path = \"../{dir}\" }}\n");
+    let mut out = String::new();
+    let mut in_deps = false;
+    let mut added = false;
+    for line in manifest.lines() {
+        if line.starts_with('[') {
+            if in_deps && !added {
+                // end of the dependencies, and all days are before this one
+                while out.ends_with("\n\n") {
+                    out.pop();
+                }
+                out.push_str(&dep);
+                out.push('\n');
+                added = true;
+            }
+            in_deps = line.trim() == "[dependencies]";
+        } else if in_deps && !added && line.starts_with("day")
+            && first_number(line).parse::<u32>().is_ok_and(|n| n > day) {
+            out.push_str(&dep);
+            added = true;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    if !added {
+        if !in_deps {
+            out.push_str("\n[dependencies]\n");
+        }
+        out.push_str(&dep);
+    }
+    out
+}
+
+// Create a new crate for a puzzle in the root directory, with a skeleton solve function and test,
+// and add it to the aoc crate. Returns the directory created.
+// Puzzles of another year than the default go in a directory named after the year, and the year
+// is added to the crate name, to keep it unique.
+pub fn new_day(rootdir: &Path, year: Option<u16>, day: u32, name: &str) -> io::Result<PathBuf> {
+    let dir = day_dir_name(day, name);
+    let (parent, crate_name, reldir, exrunner) = match year {
+        None => (rootdir.to_path_buf(), dir.clone(), dir.clone(), "../exrunner"),
+        Some(y) => (rootdir.join(y.to_string()), format!("{dir}_{y}"), format!("{y}/{dir}"), "../../exrunner"),
+    };
+    // the puzzle number must be unique, no matter what the rest of the directory name is
+    if parent.is_dir() {
+        for entry in fs::read_dir(&parent)? {
+            let existing = entry?.file_name().to_string_lossy().to_string();
+            if existing.starts_with("day") && first_number(&existing) == day.to_string() {
+                return Err(io::Error::new(ErrorKind::AlreadyExists, format!("day {day} already exists in {existing}")));
+            }
+        }
+    }
+    let manifest_path = rootdir.join("aoc").join("Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path)?;
+
+    let daydir = parent.join(&dir);
+    fs::create_dir_all(daydir.join("src"))?;
+    fs::create_dir_all(daydir.join("input"))?;
+    fs::write(daydir.join("Cargo.toml"), cargo_toml(&crate_name, exrunner))?;
+    fs::write(daydir.join("src").join("lib.rs"), lib_rs(day))?;
+    fs::write(manifest_path, add_dependency(&manifest, &crate_name, &reldir, day))?;
+    Ok(daydir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_dir_name() {
+        assert_eq!(day_dir_name(17, "clumsy crucible"), "day17_clumsy_crucible");
+        assert_eq!(day_dir_name(4, " Scratch-Cards! "), "day4_scratch_cards");
+        assert_eq!(day_dir_name(25, ""), "day25");
+    }
+
+    #[test]
+    fn test_add_dependency() {
+        let manifest = "[package]\nname = \"aoc\"\n\n[dependencies]\nclap = \"4\"\nday1_a = { path = \"../day1_a\" }\nday10_b = { path = \"../day10_b\" }\n";
+        let added = add_dependency(manifest, "day2_c", "day2_c", 2);
+        assert!(added.contains("day1_a = { path = \"../day1_a\" }\nday2_c = { path = \"../day2_c\" }\nday10_b"), "{added}");
+        let added = add_dependency(manifest, "day17_d", "day17_d", 17);
+        assert!(added.ends_with("day10_b = { path = \"../day10_b\" }\nday17_d = { path = \"../day17_d\" }\n"), "{added}");
+        let added = add_dependency(&format!("{manifest}\n[dev-dependencies]\n"), "day17_d", "day17_d", 17);
+        assert!(added.ends_with("day17_d = { path = \"../day17_d\" }\n\n[dev-dependencies]\n"), "{added}");
+    }
+
+    #[test]
+    fn test_new_day() {
+        let root = std::env::temp_dir().join(format!("aoc-scaffold-test-{}", std::process::id()));
+        fs::create_dir_all(root.join("aoc")).unwrap();
+        fs::create_dir_all(root.join("day1_a")).unwrap();
+        fs::write(root.join("aoc").join("Cargo.toml"), "[dependencies]\nday1_a = { path = \"../day1_a\" }\n").unwrap();
+        let daydir = new_day(&root, None, 17, "clumsy crucible").unwrap();
+        assert_eq!(daydir, root.join("day17_clumsy_crucible"));
+        assert!(daydir.join("input").is_dir());
+        let lib = fs::read_to_string(daydir.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("ExRunner::run(\"day 17\".to_string(), solve, test_input())"));
+        let manifest = fs::read_to_string(root.join("aoc").join("Cargo.toml")).unwrap();
+        assert!(manifest.ends_with("day17_clumsy_crucible = { path = \"../day17_clumsy_crucible\" }\n"), "{manifest}");
+        let err = new_day(&root, None, 1, "other name").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        // another year has its own directory, and can have the same day
+        let daydir = new_day(&root, Some(2022), 1, "calorie counting").unwrap();
+        assert_eq!(daydir, root.join("2022").join("day1_calorie_counting"));
+        let cargo = fs::read_to_string(daydir.join("Cargo.toml")).unwrap();
+        assert!(cargo.contains("name = \"day1_calorie_counting_2022\"") && cargo.contains("path = \"../../exrunner\""), "{cargo}");
+        assert!(cargo.contains("[package.metadata.aoc]\n"), "{cargo}");
+        let manifest = fs::read_to_string(root.join("aoc").join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("day1_calorie_counting_2022 = { path = \"../2022/day1_calorie_counting\" }\n"), "{manifest}");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use crate::example::strip_tags;
+use crate::fetch::{Http, InputProvider, FetchError};
+
+// name of the file in the root directory that keeps track of submitted answers
+pub const LEDGER_FILE: &str = "submissions.json";
+
+// What the site said about a submitted answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    // wrong, with "too high" or "too low" if the site said so, and how long to wait before the next try
+    Wrong { hint: Option<Hint>, wait: Option<Duration> },
+    // an answer was given too recently
+    Wait(Duration),
+    // this part was already solved, or cannot be solved yet
+    WrongLevel,
+    Unknown(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Hint {
+    High,
+    Low,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "That's the right answer!"),
+            Outcome::Wrong { hint, wait } => {
+                write!(f, "That's not the right answer")?;
+                match hint {
+                    Some(Hint::High) => write!(f, ", it is too high")?,
+                    Some(Hint::Low) => write!(f, ", it is too low")?,
+                    None => (),
+                }
+                match wait {
+                    Some(w) => write!(f, ". Wait {w:?} before trying again."),
+                    None => write!(f, "."),
+                }
+            },
+            Outcome::Wait(w) => write!(f, "An answer was given too recently, wait {w:?}."),
+            Outcome::WrongLevel => write!(f, "Not solving the right level. Already solved?"),
+            Outcome::Unknown(text) => write!(f, "Unexpected response: {text}"),
+        }
+    }
+}
+
+// parse a duration like "5m 12s" or "41s"
+fn parse_wait(s: &str) -> Option<Duration> {
+    let mut secs = 0;
+    let mut found = false;
+    for word in s.split_whitespace() {
+        let (num, mult) = if let Some(n) = word.strip_suffix('m') {
+            (n, 60)
+        } else if let Some(n) = word.strip_suffix('s') {
+            (n, 1)
+        } else {
+            break;
+        };
+        secs += num.parse::<u64>().ok()? * mult;
+        found = true;
+    }
+    found.then(|| Duration::from_secs(secs))
+}
+
+// Find out what the response to submitting an answer means. The message is in the article of the page.
+pub fn parse_response(html: &str) -> Outcome {
+    let text = html.split_once("<article>")
+        .and_then(|(_, a)| a.split_once("</article>"))
+        .map(|(a, _)| strip_tags(a))
+        .unwrap_or_else(|| strip_tags(html));
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.contains("That's the right answer") {
+        Outcome::Correct
+    } else if text.contains("That's not the right answer") {
+        let hint = if text.contains("too high") {
+            Some(Hint::High)
+        } else if text.contains("too low") {
+            Some(Hint::Low)
+        } else {
+            None
+        };
+        let lower = text.to_lowercase();
+        let wait = if lower.contains("wait one minute") {
+            Some(Duration::from_secs(60))
+        } else {
+            lower.split_once("please wait ")
+                .and_then(|(_, w)| w.split_once(" minutes"))
+                .and_then(|(n, _)| n.parse::<u64>().ok())
+                .map(|n| Duration::from_secs(n * 60))
+        };
+        Outcome::Wrong { hint, wait }
+    } else if text.contains("You gave an answer too recently") {
+        let wait = text.split_once("You have ")
+            .and_then(|(_, w)| w.split_once(" left to wait"))
+            .and_then(|(w, _)| parse_wait(w));
+        Outcome::Wait(wait.unwrap_or(Duration::from_secs(60)))
+    } else if text.contains("You don't seem to be solving the right level") {
+        Outcome::WrongLevel
+    } else {
+        Outcome::Unknown(text)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WrongAnswer {
+    pub answer: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<Hint>,
+}
+
+// what is known about the answers to one part of a puzzle
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PartLedger {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correct: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wrong: Vec<WrongAnswer>,
+    // no answers should be sent before this time, in seconds since the epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait_until: Option<u64>,
+}
+
+impl PartLedger {
+    // Check if an answer is worth submitting. Returns why not, if it is not.
+    pub fn check(&self, answer: &str, now: u64) -> Result<(), String> {
+        if let Some(c) = &self.correct {
+            return Err(if c == answer {
+                format!("{answer} is already known to be correct")
+            } else {
+                format!("the correct answer is already known: {c}")
+            });
+        }
+        if self.wrong.iter().any(|w| w.answer == answer) {
+            return Err(format!("{answer} was already submitted, and was wrong"));
+        }
+        // compare with answers that were too high or too low, if these are numbers
+        if let Ok(num) = answer.parse::<i128>() {
+            for w in &self.wrong {
+                match (w.hint, w.answer.parse::<i128>()) {
+                    (Some(Hint::High), Ok(high)) if num >= high => return Err(format!("{high} was already too high")),
+                    (Some(Hint::Low), Ok(low)) if num <= low => return Err(format!("{low} was already too low")),
+                    _ => (),
+                }
+            }
+        }
+        if let Some(until) = self.wait_until.filter(|&u| u > now) {
+            return Err(format!("need to wait {}s before submitting again", until - now));
+        }
+        Ok(())
+    }
+
+    pub fn record(&mut self, answer: &str, outcome: &Outcome, now: u64) {
+        match outcome {
+            Outcome::Correct => self.correct = Some(answer.to_string()),
+            Outcome::Wrong { hint, wait } => {
+                self.wrong.push(WrongAnswer { answer: answer.to_string(), hint: *hint });
+                self.wait_until = wait.map(|w| now + w.as_secs());
+            },
+            Outcome::Wait(w) => self.wait_until = Some(now + w.as_secs()),
+            Outcome::WrongLevel | Outcome::Unknown(_) => (),
+        }
+    }
+}
+
+// All submitted answers, stored as JSON in the root directory. The key is the year, day and part.
+pub struct Ledger {
+    path: PathBuf,
+    parts: BTreeMap<String, PartLedger>,
+}
+
+impl Ledger {
+    // load the ledger. If the file does not exist, nothing was submitted yet.
+    pub fn load(rootdir: &Path) -> io::Result<Ledger> {
+        let path = rootdir.join(LEDGER_FILE);
+        let parts = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Ledger { path, parts })
+    }
+
+    pub fn key(year: u16, day: u32, part: u8) -> String {
+        format!("{year}/day{day}/part{part}")
+    }
+
+    pub fn part(&mut self, key: &str) -> &mut PartLedger {
+        self.parts.entry(key.to_string()).or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut contents = serde_json::to_string_pretty(&self.parts)?;
+        contents.push('\n');
+        fs::write(&self.path, contents)
+    }
+}
+
+// Why an answer was not submitted
+#[derive(Debug)]
+pub enum SubmitError {
+    NoAnswer(String),
+    Refused(String),
+    Fetch(FetchError),
+    Io(io::Error),
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitError::NoAnswer(why) => write!(f, "no answer to submit: {why}"),
+            SubmitError::Refused(why) => write!(f, "not submitting, {why}"),
+            SubmitError::Fetch(e) => write!(f, "{e}"),
+            SubmitError::Io(e) => write!(f, "cannot update {LEDGER_FILE}: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// Submit an answer, unless the ledger says that is pointless. The outcome is recorded in the ledger.
+pub fn submit<H: Http>(provider: &InputProvider<H>, ledger: &mut Ledger, day: u32, part: u8, answer: &str) -> Result<Outcome, SubmitError> {
+    let key = Ledger::key(provider.year(), day, part);
+    ledger.part(&key).check(answer, now()).map_err(SubmitError::Refused)?;
+    let url = format!("{}/answer", provider.puzzle_url(day));
+    let level = part.to_string();
+    let html = provider.post(&url, &[("level", &level), ("answer", answer)]).map_err(SubmitError::Fetch)?;
+    let outcome = parse_response(&String::from_utf8_lossy(&html));
+    ledger.part(&key).record(answer, &outcome, now());
+    ledger.save().map_err(SubmitError::Io)?;
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::fetch::HttpResponse;
+
+    fn page(msg: &str) -> String {
+        format!("<html><body><main>\n<article><p>{msg}</p></article>\n</main></body></html>")
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(parse_response(&page("That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer.")), Outcome::Correct);
+        assert_eq!(parse_response(&page("That's not the right answer; your answer is too high.  If you're stuck, ask for hints.  \
+            Please wait one minute before trying again. <a href=\"/2023/day/1\">[Return to Day 1]</a>")),
+            Outcome::Wrong { hint: Some(Hint::High), wait: Some(Duration::from_secs(60)) });
+        assert_eq!(parse_response(&page("That's not the right answer; your answer is too low.  please wait 5 minutes before trying again.")),
+            Outcome::Wrong { hint: Some(Hint::Low), wait: Some(Duration::from_secs(300)) });
+        assert_eq!(parse_response(&page("That's not the right answer.")), Outcome::Wrong { hint: None, wait: None });
+        assert_eq!(parse_response(&page("You gave an answer too recently; you have to wait after submitting an answer before trying again.  \
+            You have 5m 12s left to wait.")), Outcome::Wait(Duration::from_secs(312)));
+        assert_eq!(parse_response(&page("You gave an answer too recently. You have 41s left to wait.")), Outcome::Wait(Duration::from_secs(41)));
+        assert_eq!(parse_response(&page("You don't seem to be solving the right level.  Did you already complete it?")), Outcome::WrongLevel);
+        assert_eq!(parse_response("<p>Something else</p>"), Outcome::Unknown("Something else".to_string()));
+    }
+
+    #[test]
+    fn test_check() {
+        let mut l = PartLedger::default();
+        assert!(l.check("100", 0).is_ok());
+        l.record("100", &Outcome::Wrong { hint: Some(Hint::High), wait: Some(Duration::from_secs(60)) }, 1000);
+        assert_eq!(l.check("50", 1030), Err("need to wait 30s before submitting again".to_string()));
+        assert!(l.check("50", 1060).is_ok());
+        assert_eq!(l.check("100", 1060), Err("100 was already submitted, and was wrong".to_string()));
+        assert_eq!(l.check("120", 1060), Err("100 was already too high".to_string()));
+        l.record("20", &Outcome::Wrong { hint: Some(Hint::Low), wait: None }, 1060);
+        assert_eq!(l.check("15", 1060), Err("20 was already too low".to_string()));
+        assert!(l.check("abc", 1060).is_ok());
+        l.record("42", &Outcome::Correct, 1100);
+        assert_eq!(l.check("43", 1100), Err("the correct answer is already known: 42".to_string()));
+    }
+
+    // Stand-in for the site, answering every post with the same page
+    struct FakeSite {
+        response: String,
+        posts: Rc<RefCell<Vec<(String, String)>>>,
+    }
+
+    impl Http for FakeSite {
+        fn get(&self, _url: &str, _headers: &[(&str, &str)]) -> Result<HttpResponse, String> {
+            Err("not expected".to_string())
+        }
+
+        fn post(&self, url: &str, _headers: &[(&str, &str)], form: &[(&str, &str)]) -> Result<HttpResponse, String> {
+            let form = form.iter().map(|(k, v)| format!("{k}={v}")).collect::<Vec<_>>().join("&");
+            self.posts.borrow_mut().push((url.to_string(), form));
+            Ok(HttpResponse { status: 200, body: self.response.clone().into_bytes() })
+        }
+    }
+
+    #[test]
+    fn test_submit() {
+        let dir = std::env::temp_dir().join(format!("aoc-submit-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let posts = Rc::new(RefCell::new(Vec::new()));
+        let site = FakeSite { response: page("That's not the right answer; your answer is too low."), posts: posts.clone() };
+        let provider = InputProvider::with_http(site, "http://aoc.test", "abc", 2023);
+        let mut ledger = Ledger::load(&dir).unwrap();
+        let outcome = submit(&provider, &mut ledger, 16, 2, "1234").unwrap();
+        assert_eq!(outcome, Outcome::Wrong { hint: Some(Hint::Low), wait: None });
+        assert_eq!(*posts.borrow(), vec![("http://aoc.test/2023/day/16/answer".to_string(), "level=2&answer=1234".to_string())]);
+        // the same answer is not sent again, even after loading the ledger again
+        let mut ledger = Ledger::load(&dir).unwrap();
+        assert!(matches!(submit(&provider, &mut ledger, 16, 2, "1234"), Err(SubmitError::Refused(_))));
+        assert!(matches!(submit(&provider, &mut ledger, 16, 2, "1000"), Err(SubmitError::Refused(_))));
+        // the other part is separate
+        assert!(submit(&provider, &mut ledger, 16, 1, "1234").is_ok());
+        assert_eq!(posts.borrow().len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
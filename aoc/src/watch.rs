@@ -0,0 +1,189 @@
+use std::env;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::time::Duration;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use serde_json::Value;
+use exrunner::duration_format;
+use crate::{CliArgs, Day};
+
+// wait this long after a change, so a burst of changes (like an editor saving) gives one run
+const SETTLE: Duration = Duration::from_millis(200);
+
+// What is needed of a run to compare it with the next one
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    pub answers: Vec<Option<String>>,
+    // parse, part 1, part 2 and total, in nanoseconds
+    pub times: [Option<u64>; 4],
+    pub error: Option<String>,
+}
+
+const STAGES: [&str; 4] = ["parse", "time1", "time2", "total"];
+
+impl RunSummary {
+    // read the summary from the JSON report of a run of a single puzzle
+    pub fn from_json(json: &str) -> Option<RunSummary> {
+        let v: Value = serde_json::from_str(json).ok()?;
+        let day = v.as_array()?.first()?;
+        // integer answers are numbers, the others strings
+        let answers = day["answers"].as_array()?.iter().map(|a| match a {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }).collect();
+        let times = STAGES.map(|s| day[format!("{s}_ns")].as_u64());
+        let error = day["error"].as_str().map(String::from);
+        Some(RunSummary { answers, times, error })
+    }
+
+    // Describe what changed since the previous run
+    pub fn compare(&self, prev: &RunSummary) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.error.is_some() || prev.error.is_some() {
+            if self.error != prev.error {
+                lines.push(format!("error: {} (was {})", self.error.as_deref().unwrap_or("none"), prev.error.as_deref().unwrap_or("none")));
+            }
+            return lines;
+        }
+        for (i, answ) in self.answers.iter().enumerate() {
+            let before = prev.answers.get(i).cloned().flatten();
+            match (answ, &before) {
+                (Some(a), Some(b)) if a == b => lines.push(format!("part{}: {a} (unchanged)", i + 1)),
+                (Some(a), Some(b)) => lines.push(format!("part{}: {a} (was {b})", i + 1)),
+                (Some(a), None) => lines.push(format!("part{}: {a} (new)", i + 1)),
+                (None, Some(b)) => lines.push(format!("part{}: no answer (was {b})", i + 1)),
+                (None, None) => (),
+            }
+        }
+        for (i, stage) in STAGES.iter().enumerate() {
+            if let (Some(cur), Some(before)) = (self.times[i], prev.times[i]) {
+                let pct = (cur as f64 / before.max(1) as f64 - 1.0) * 100.0;
+                lines.push(format!("{stage}: {} (was {}, {pct:+.1}%)",
+                    duration_format(&Duration::from_nanos(cur)), duration_format(&Duration::from_nanos(before))));
+            }
+        }
+        lines
+    }
+}
+
+// arguments to run the puzzle once, with raw output, the way it is watched
+fn child_args(args: &CliArgs, year: u16, day: &Day, report: &Path) -> Vec<String> {
+    let mut child = vec!["--year".to_string(), year.to_string(), day.number().to_string(), "--raw".to_string()];
+    child.extend(["--report-file".to_string(), report.to_string_lossy().to_string()]);
+    if let Some(i) = &args.input {
+        // the puzzle runs from the root directory, so a path must not depend on the current directory
+        let i = match i.contains('/') {
+            true => std::path::absolute(i).map(|p| p.to_string_lossy().to_string()).unwrap_or_else(|_| i.clone()),
+            false => i.clone(),
+        };
+        child.extend(["--input".to_string(), i]);
+    }
+    if let Some(p) = args.part {
+        child.extend(["--part".to_string(), p.to_string()]);
+    }
+    if args.verbose > 0 {
+        child.push(format!("-{}", "v".repeat(args.verbose as usize)));
+    }
+    for spec in &args.debug {
+        child.extend(["--debug".to_string(), spec.to_string()]);
+    }
+    if args.no_progress {
+        child.push("--no-progress".to_string());
+    }
+    if let Some(t) = args.timeout {
+        child.extend(["--timeout".to_string(), t.as_secs_f64().to_string()]);
+    }
+    child
+}
+
+// build the aoc binary, in the same profile as the running one
+fn build(rootdir: &Path, exe: &Path) -> io::Result<bool> {
+    let mut cmd = Command::new(env::var("CARGO").unwrap_or_else(|_| String::from("cargo")));
+    cmd.args(["build", "-p", "aoc"]).current_dir(rootdir);
+    if exe.components().any(|c| c.as_os_str() == "release") {
+        cmd.arg("--release");
+    }
+    Ok(cmd.status()?.success())
+}
+
+// Watch the source and input of a puzzle, and rebuild and run it again on every change. Only returns on errors.
+pub fn watch(rootdir: &Path, day: &Day, year: u16, args: &CliArgs) -> io::Result<()> {
+    let exe = env::current_exe()?;
+    let daydir = rootdir.join(day.dir);
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |ev: notify::Result<Event>| {
+        if let Ok(ev) = ev {
+            if matches!(ev.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                let _ = tx.send(ev.paths);
+            }
+        }
+    }).map_err(io::Error::other)?;
+    let watched: Vec<PathBuf> = ["src", "input"].iter().map(|d| daydir.join(d)).filter(|d| d.is_dir()).collect();
+    if watched.is_empty() {
+        return Err(io::Error::new(ErrorKind::NotFound, format!("nothing to watch in {}", daydir.to_string_lossy())));
+    }
+    for d in &watched {
+        watcher.watch(d, RecursiveMode::Recursive).map_err(io::Error::other)?;
+    }
+    let report = env::temp_dir().join(format!("aoc-watch-{}.json", std::process::id()));
+    let mut prev: Option<RunSummary> = None;
+    loop {
+        if build(rootdir, &exe)? {
+            let _ = fs::remove_file(&report);
+            Command::new(&exe).args(child_args(args, year, day, &report)).current_dir(rootdir).status()?;
+            let cur = fs::read_to_string(&report).ok().and_then(|j| RunSummary::from_json(&j));
+            if let (Some(cur), Some(prev)) = (&cur, &prev) {
+                println!("--- compared to the previous run:");
+                cur.compare(prev).iter().for_each(|l| println!("{l}"));
+            }
+            prev = cur.or(prev);
+        } else {
+            eprintln!("Build failed");
+        }
+        let dirs: Vec<_> = watched.iter().map(|d| d.to_string_lossy()).collect();
+        eprintln!("Watching {} for changes...", dirs.join(" and "));
+        let mut changed = rx.recv().map_err(io::Error::other)?;
+        // let a burst of changes settle
+        std::thread::sleep(SETTLE);
+        while let Ok(more) = rx.try_recv() {
+            changed.extend(more);
+        }
+        println!("=== {} changed", changed.first().map(|p| p.to_string_lossy().to_string()).unwrap_or_default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"[{"day": "day9_mirage_maintenance", "input": "input.txt", "status": "ok", "answers": [114, "2"], "labels": [null, null],
+        "verdicts": ["pass", "unknown"], "parse_ns": 1000, "time1_ns": 2000000, "time2_ns": 500, "cleanup_ns": 10, "total_ns": 2001510}]"#;
+
+    #[test]
+    fn test_from_json() {
+        let s = RunSummary::from_json(REPORT).unwrap();
+        assert_eq!(s.answers, vec![Some("114".to_string()), Some("2".to_string())]);
+        assert_eq!(s.times, [Some(1000), Some(2000000), Some(500), Some(2001510)]);
+        assert_eq!(s.error, None);
+        assert_eq!(RunSummary::from_json("[]"), None);
+    }
+
+    #[test]
+    fn test_compare() {
+        let prev = RunSummary::from_json(REPORT).unwrap();
+        let cur = RunSummary { answers: vec![Some("114".to_string()), Some("3".to_string())], times: [Some(1000), Some(1000000), None, Some(1001010)], error: None };
+        assert_eq!(cur.compare(&prev), vec![
+            "part1: 114 (unchanged)",
+            "part2: 3 (was 2)",
+            "parse: 1µs (was 1µs, +0.0%)",
+            "time1: 1ms (was 2ms, -50.0%)",
+            "total: 1.00ms (was 2.00ms, -50.0%)",
+        ]);
+        let failed = RunSummary { error: Some("FAILED: oops".to_string()), ..Default::default() };
+        assert_eq!(failed.compare(&prev), vec!["error: FAILED: oops (was none)"]);
+    }
+}
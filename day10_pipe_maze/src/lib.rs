@@ -1,137 +1,90 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleError, PuzzleResult};
+use grid::{Grid, Pos, Dir, DirSet};
 use std::io::BufRead;
 use phf::phf_map;
 
 struct Pipemaze {
-    field: Vec<Vec<u8>>,
-    startpos: (usize, usize),
+    field: Grid<u8>,
+    startpos: Pos,
 }
 
-fn parse(input: impl BufRead) -> Pipemaze {
-    let field: Vec<Vec<_>> = input.lines().map(|l| {
-        let line = l.expect("Error reading input");
-        line.as_bytes().to_vec()
-    }).collect();
-    let mut findstart = field.iter().enumerate().flat_map(|(y, l)| {
-        l.iter().enumerate().filter_map(move |(x, c)| {
-            if *c == b'S' {
-                Some((x, y))
-            } else {
-                None
-            }
-        })
-    });
-    let startpos = findstart.next().expect("No S startpos in input");
-    if findstart.next().is_some() {
-        panic!("Too many startpos S in input");
+fn parse(input: impl BufRead) -> PuzzleResult<Pipemaze> {
+    let field = Grid::parse(input)?;
+    let starts: Vec<_> = field.iter().filter(|(_, &c)| c == b'S').map(|(pos, _)| pos).collect();
+    let startpos = *starts.first().ok_or_else(|| PuzzleError::new("no S startpos in input"))?;
+    if let Some(pos) = starts.get(1) {
+        return Err(PuzzleError::at(pos.y as usize + 1, pos.x as usize + 1, "too many startpos S in input"));
     }
-    Pipemaze{ field, startpos }
+    Ok(Pipemaze{ field, startpos })
 }
 
-type DIR = u8;
-const NORTH: DIR = 1;
-const WEST: DIR = 2;
-const SOUTH: DIR = 4;
-const EAST: DIR = 8;
+// a pipe connecting two directions
+const fn pipe(a: Dir, b: Dir) -> DirSet {
+    DirSet::EMPTY.with(a).with(b)
+}
 
 // PIPES is used as the lookup table for character -> pipe directions.
-static PIPES: phf::Map<u8, DIR> = phf_map! {
-    b'|' => NORTH | SOUTH,
-    b'-' => EAST | WEST,
-    b'L' => NORTH | EAST,
-    b'J' => NORTH | WEST,
-    b'7' => SOUTH | WEST,
-    b'F' => SOUTH | EAST,
+static PIPES: phf::Map<u8, DirSet> = phf_map! {
+    b'|' => pipe(Dir::North, Dir::South),
+    b'-' => pipe(Dir::East, Dir::West),
+    b'L' => pipe(Dir::North, Dir::East),
+    b'J' => pipe(Dir::North, Dir::West),
+    b'7' => pipe(Dir::South, Dir::West),
+    b'F' => pipe(Dir::South, Dir::East),
 };
 
-// mirror a direction
-fn mirror_dir(dir: DIR) -> DIR {
-    if dir & (NORTH|SOUTH) != 0 {
-        dir ^ (NORTH|SOUTH)
-    } else {
-        dir ^ (EAST|WEST)
-    }
-}
-
-// Walk the pipe for 1 step. Returns the new position and direction, or None if not possible
-fn walk_pipe(pm: &Pipemaze, pos: (usize, usize), dir: DIR) -> Option<((usize, usize), DIR)> {
-    let (dx, dy) = if dir == NORTH {
-        (0, -1)
-    } else if dir == WEST {
-        (-1, 0)
-    } else if dir == SOUTH {
-        (0, 1)
-    } else if dir == EAST {
-        (1, 0)
-    } else {
-        panic!("Unknown direction {} at position ({},{})", dir, pos.0, pos.1);
-    };
-    if pos.0 == 0 && dx == -1 {
-        return None;
-    }
-    if pos.1 == 0 && dy == -1 {
-        return None;
-    }
-    if pos.1 as i32 + dy >= pm.field.len() as i32 {
-        return None;
-    }
-    let newy = (pos.1 as i32 + dy) as usize;
-    if pos.0 as i32 + dx >= pm.field[newy].len() as i32 {
-        return None;
-    }
-    let newx = (pos.0 as i32 + dx) as usize;
-    let pchar = pm.field[newy][newx];
-    // println!("Walk in direction {dir} from {},{} to {newx},{newy}. Now at {pchar}", pos.0, pos.1);
+// Walk the pipe for 1 step. Returns the new position and direction, or None if not possible.
+// At the start position, the direction stays the same.
+fn walk_pipe(pm: &Pipemaze, pos: Pos, dir: Dir) -> Option<(Pos, Dir)> {
+    let newpos = pos.step(dir);
+    let pchar = *pm.field.get(newpos)?;
+    // println!("Walk in direction {dir} from {pos} to {newpos}. Now at {pchar}");
     if pchar == b'S' {
         // We've reached the starting position again
-        return Some(((newx, newy), 0 as DIR));
-    } else if let Some(pdir) = PIPES.get(&pchar) {
-        // if we walk in for direction X, the pipe should go in the mirror direction
-        let mdir = mirror_dir(dir);
-        if (mdir & *pdir) != 0 {
+        return Some((newpos, dir));
+    } else if let Some(pdirs) = PIPES.get(&pchar) {
+        // if we walk in for direction X, the pipe should go in the reverse direction
+        if pdirs.contains(dir.reverse()) {
             // we leave from the other pipe end
-            let newdir = *pdir & !mdir;
-            return Some(((newx, newy), newdir));
+            let newdir = pdirs.without(dir.reverse()).iter().next()?;
+            return Some((newpos, newdir));
         }
     }
     // either invalid pipe char, or invalid direction in incoming pipe
-    return None;
+    None
 }
 
-// Walk around in the pipemaze, starting at start pos in direction DIR
+// Walk around in the pipemaze, starting at start pos in direction Dir
 // if not possible, returns None. If it is possible, returns the path length
 // until we reach the start pos again.
 // second return value is a copy of the field with only the path itself on it
-fn walk_around(pm: &Pipemaze, startdir: DIR) -> (Option<usize>, Vec<Vec<u8>>) {
+fn walk_around(pm: &Pipemaze, startdir: Dir) -> (Option<usize>, Grid<u8>) {
     let mut pos = pm.startpos;
     let mut pathlen = 0;
     let mut dir = startdir;
-    let mut pathonly: Vec<Vec<u8>> = Vec::new();
     // fill the pathonly with dots, same size as input pipemaze
-    for l in &pm.field {
-        pathonly.push( std::iter::repeat(b'.').take(l.len()).collect() );
-    }
+    let mut pathonly = Grid::new(pm.field.width(), pm.field.height(), b'.');
     while let Some((newpos, newdir)) = walk_pipe(pm, pos, dir) {
         pathlen += 1;
         pos = newpos;
         if pos == pm.startpos {
             // determine the starting point shape size. We started with startdir, and we end with dir into the startpos
-            let startdirs = startdir | mirror_dir(dir);
+            let startdirs = pipe(startdir, dir.reverse());
             let startshape = PIPES.entries().find_map(|(&shape, &dirs)| if dirs == startdirs { Some(shape) } else { None }).expect("Unknown start directions");
-            pathonly[pos.1][pos.0] = startshape;
+            pathonly[pos] = startshape;
             return (Some(pathlen), pathonly);
         } else {
             // copy this element of the path
-            pathonly[pos.1][pos.0] = pm.field[pos.1][pos.0];
+            pathonly[pos] = pm.field[pos];
             dir = newdir;
         }
     }
     (None, pathonly)
 }
 
-fn count_enclosed(field: &Vec<Vec<u8>>) -> usize {
+fn count_enclosed(field: &Grid<u8>) -> usize {
     let mut in_path = false;
-    field.iter().map(move |l| {
+    field.rows().map(move |l| {
         let enclosed = l.iter().filter(move |&&c| {
             // pretend to scan just south of the "-" marker. Anytime we cross the path, flip the "in-path" indicator.
             // This means it flips not only on | but also on F and 7. It does not flip on L and J.
@@ -149,28 +102,23 @@ fn count_enclosed(field: &Vec<Vec<u8>>) -> usize {
     }).sum()
 }
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let pm = parse(input);
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let pm = parse(input)?;
     er.parse_done();
-    // er.debugln(&format!("Input pipemaze:\n{}", field_to_string(&pm.field)));
-    for dir in [NORTH, WEST, SOUTH, EAST] {
+    er.trace(|| format!("Input pipemaze:\n{}", pm.field));
+    for dir in Dir::ALL {
         if let (Some(pathlen), pathonly) = walk_around(&pm, dir) {
             er.part1(pathlen / 2, Some("Halfway of loop length"));
-            // er.debugln(&format!("Path only:\n{}", field_to_string(&pathonly)));
+            er.trace(|| format!("Path only:\n{pathonly}"));
             let enclosed_tiles = count_enclosed(&pathonly);
             er.part2(enclosed_tiles, Some("Number of enclosed tiles"));
-            break;
+            return Ok(());
         }
     }
+    let Pos { x, y } = pm.startpos;
+    Err(PuzzleError::at(y as usize + 1, x as usize + 1, "no loop through the startpos"))
 }
 
-// fn field_to_string(field: &Vec<Vec<u8>>) -> String {
-//     let lines: Vec<_> = field.iter().map(|l| {
-//         str::from_utf8(l).unwrap()
-//     }).collect();
-//     lines.join("\n")
-// }
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,68 +1,52 @@
-use exrunner::ExRunner;
-use std::{io::BufRead, collections::HashSet};
+use exrunner::{ExRunner, PuzzleError, PuzzleResult};
+use grid::{Grid, Pos};
+use std::io::BufRead;
 
-fn parse(input: impl BufRead) -> Vec<(i64, i64)> {
-    input.lines().enumerate().flat_map(|(y, l)| {
-        let line = l.expect("Error reading input");
-        line.chars().enumerate().filter_map(move |(x, c)| {
-            if c == '#' {
-                Some((x as i64, y as i64))
-            } else {
-                None
-            }
-        }).collect::<Vec<_>>().into_iter()
-    }).collect()
+fn parse(input: impl BufRead) -> PuzzleResult<Grid<u8>> {
+    let sky = Grid::parse(input)?;
+    if !sky.iter().any(|(_, &c)| c == b'#') {
+        return Err(PuzzleError::new("no galaxies in input"));
+    }
+    Ok(sky)
 }
 
-static mut BIG_EXPANSION: i64 = 1000000;
+const BIG_EXPANSION: i64 = 1000000;
+
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    solve_expansion(input, er, BIG_EXPANSION)
+}
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let galaxies = parse(input);
-    // determine field size
-    let xmax = galaxies.iter().map(|(x, _)| *x).max().unwrap() + 1;
-    let ymax = galaxies.iter().map(|(_, y)| *y).max().unwrap() + 1;
+// solve, with the given expansion factor for part 2
+fn solve_expansion(input: impl BufRead, er: &mut ExRunner, expansion: i64) -> PuzzleResult {
+    let sky = parse(input)?;
     er.parse_done();
-    // determine row/cols that do not appear in input
-    let mut sparse_x = HashSet::new();
-    for x in 0..xmax {
-        sparse_x.insert(x);
-    }
-    let mut sparse_y = HashSet::new();
-    for y in 0..ymax {
-        sparse_y.insert(y);
-    }
-    for (xg, yg) in &galaxies {
-        sparse_x.remove(xg);
-        sparse_y.remove(yg);
-    }
+    let galaxies: Vec<Pos> = sky.iter().filter(|(_, &c)| c == b'#').map(|(pos, _)| pos).collect();
+    // determine row/cols that do not have galaxies
+    let sparse_x: Vec<i64> = (0..sky.width()).filter(|&x| sky.column(x).all(|&c| c != b'#')).map(|x| x as i64).collect();
+    let sparse_y: Vec<i64> = sky.rows().zip(0..).filter(|(row, _)| !row.contains(&b'#')).map(|(_, y)| y).collect();
     // now expand the voids
-    let expand_galaxies: Vec<_> = galaxies.iter().map(|&(x, y)| {
-        (x + sparse_x.iter().filter(|&&sx| sx < x).count() as i64,
-        y + sparse_y.iter().filter(|&&sy| sy < y).count() as i64)
-    }).collect();
-    // er.debugln(&format!("Expanded galaxies at positions: {:?}", expand_galaxies));
+    let expand = |size: i64| -> Vec<Pos> {
+        galaxies.iter().map(|&g| {
+            Pos::new(g.x + sparse_x.iter().filter(|&&sx| sx < g.x).count() as i64 * size,
+                g.y + sparse_y.iter().filter(|&&sy| sy < g.y).count() as i64 * size)
+        }).collect()
+    };
+    let expand_galaxies = expand(1);
+    er.trace(|| format!("Expanded galaxies at positions: {:?}", expand_galaxies));
     er.part1(sum_dist_pairs(&expand_galaxies), Some("Distance between expanded galaxies"));
-    // no multi-threading, so it's safe...
-    let big = unsafe { BIG_EXPANSION - 1 };
-    let bigexpand_galaxies: Vec<_> = galaxies.iter().map(|&(x, y)| {
-        (x + sparse_x.iter().filter(|&&sx| sx < x).count() as i64 * big,
-        y + sparse_y.iter().filter(|&&sy| sy < y).count() as i64 * big)
-    }).collect();
+    let bigexpand_galaxies = expand(expansion - 1);
     er.part2(sum_dist_pairs(&bigexpand_galaxies), Some("Distance between big expanded galaxies"));
+    Ok(())
 }
 
-fn sum_dist_pairs(glx: &Vec<(i64, i64)>) -> i64 {
+fn sum_dist_pairs(glx: &[Pos]) -> i64 {
     (0..glx.len()-1).flat_map(|g1| {
         (g1..glx.len()).map(move |g2| {
-            galaxy_dist(&glx[g1], &glx[g2])
+            glx[g1].manhattan(glx[g2])
         })
     }).sum()
 }
 
-fn galaxy_dist(g1: &(i64, i64), g2: &(i64, i64)) -> i64 {
-    (g1.0 - g2.0).abs() + (g1.1 - g2.1).abs()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,17 +70,11 @@ mod tests {
 
     #[test]
     fn test_part12() {
-        unsafe {
-            BIG_EXPANSION = 10;
-        }
-        let er = ExRunner::run("day 11".to_string(), solve, test_input());
+        let er = ExRunner::run("day 11".to_string(), |i, r| solve_expansion(i, r, 10), test_input());
         er.print_raw();
         assert_eq!(er.answ()[0], Some("374".to_string()));
         assert_eq!(er.answ()[1], Some("1030".to_string()));
-        unsafe {
-            BIG_EXPANSION = 100;
-        }
-        let er = ExRunner::run("day 11".to_string(), solve, test_input());
+        let er = ExRunner::run("day 11".to_string(), |i, r| solve_expansion(i, r, 100), test_input());
         er.print_raw();
         assert_eq!(er.answ()[1], Some("8410".to_string()));
     }
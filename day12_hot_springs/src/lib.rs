@@ -1,11 +1,11 @@
-use exrunner::ExRunner;
-use std::{io::BufRead, collections::HashMap};
-// use std::time::Instant;
+use exrunner::{ExRunner, Level, PuzzleError, PuzzleResult, parse_num, numbered_lines};
+use std::{io::BufRead, collections::HashMap, time::Instant};
 
-#[derive(Debug)]
+// counted locally, the recursion is too hot to report every lookup
+#[derive(Debug, Default)]
 struct CacheStats {
-    cache_hit: i32,
-    cache_miss: i32,
+    cache_hit: u64,
+    cache_miss: u64,
 }
 
 #[derive(PartialEq, Eq, Hash)]
@@ -14,45 +14,63 @@ struct SpringCacheEntry {
     runs: Vec<i32>,
 }
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
     let mut total_pos = 0;
-    let mut cs = CacheStats{ cache_hit: 0, cache_miss: 0 };
     // create a cache for spring positions & runs to possibilities.
     let mut springruncache = HashMap::new();
+    let mut cs = CacheStats::default();
     let mut inbuf = Vec::new();
-    for l in input.lines() {
-        let line = l.expect("Error reading input");
-        let (springs, runstr) = line.split_once(' ').expect("Need space in input");
-        let runs: Vec<i32> = runstr.split(',').map(|n| n.parse().expect("runs should be numeric")).collect();
-        let possibilities = spring_distributions(springs.as_bytes(), &runs, &mut springruncache, &mut cs);
+    for l in numbered_lines(input) {
+        let (lnum, line) = l?;
+        let (springs, runstr) = line.split_once(' ').ok_or_else(|| PuzzleError::at_line(lnum, "need space in input"))?;
+        if let Some(pos) = springs.find(|c| !".#?".contains(c)) {
+            return Err(PuzzleError::at(lnum, pos + 1, format!("invalid spring '{}'", &springs[pos..].chars().next().unwrap())));
+        }
+        let runs: Vec<i32> = runstr.split(',').map(parse_num).collect::<PuzzleResult<_>>().map_err(|e| e.with_line(lnum))?;
+        if runs.iter().any(|&r| r <= 0) {
+            return Err(PuzzleError::at_line(lnum, "runs should be positive"));
+        }
+        if er.wants_part(1) {
+            let possibilities = spring_distributions(springs.as_bytes(), &runs, &mut springruncache, &mut cs);
+            er.trace(|| format!("Line: {}, possible solutions: {}", line, possibilities));
+            total_pos += possibilities;
+        }
         inbuf.push((springs.to_string(), runs));
-        // er.debugln(&format!("Line: {}, possible solutions: {}", line, possibilities));
-        total_pos += possibilities;
     }
-    er.part1(total_pos, Some("Sum of all possible solutions"));
+    if er.wants_part(1) {
+        er.part1(total_pos, Some("Sum of all possible solutions"));
+    }
     // now for part 2
-    let mut total_pos = 0;
-    for (springs, runs) in inbuf {
-        let longsprings = &format!("{}?{}?{}?{}?{}", springs, springs, springs, springs, springs);
-        let mut longruns = runs.to_owned();
-        for _ in 0..4 {
-            let mut copyrun = runs.to_owned();
-            longruns.append(&mut copyrun);
+    if er.wants_part(2) {
+        let mut total_pos = 0;
+        for (springs, runs) in inbuf {
+            let longsprings = &format!("{}?{}?{}?{}?{}", springs, springs, springs, springs, springs);
+            let mut longruns = runs.to_owned();
+            for _ in 0..4 {
+                let mut copyrun = runs.to_owned();
+                longruns.append(&mut copyrun);
+            }
+            // only time the lines when the timing is shown
+            let start = er.log_enabled(Level::Trace).then(Instant::now);
+            let possibilities = spring_distributions(longsprings.as_bytes(), &longruns, &mut springruncache, &mut cs);
+            if let Some(start) = start {
+                let elapsed = start.elapsed().as_micros();
+                er.trace(|| format!("Runtime {}. Longsprings {} longruns {:?} possibilities: {}", elapsed, longsprings, longruns, possibilities));
+            }
+            total_pos += possibilities;
         }
-        // let start = Instant::now();
-        let possibilities = spring_distributions(longsprings.as_bytes(), &longruns, &mut springruncache, &mut cs);
-        // let elapsed = start.elapsed().as_micros();
-        // er.debugln(&format!("Runtime {}. Longsprings {} longruns {:?} possibilities: {}", elapsed, longsprings, longruns, possibilities));
-        total_pos += possibilities;
+        er.part2(total_pos, Some("all possible solutions for long springs"));
     }
-    er.part2(total_pos, Some("all possible solutions for long springs"));
-    er.debugln(&format!("Cache stats: {:?}", cs));
+    er.count("cache hits", cs.cache_hit);
+    er.count("cache misses", cs.cache_miss);
+    er.gauge("cache size", springruncache.len() as i64);
+    Ok(())
 }
 
 fn spring_distributions(springs: &[u8], runs: &[i32], cache: &mut HashMap<SpringCacheEntry, i64>, cachestat: &mut CacheStats) -> i64 {
     // eprintln!("springs {}, runs {:?}", std::str::from_utf8(springs).unwrap(), runs);
     // easy ones first. No runs.
-    if runs.len() == 0 {
+    if runs.is_empty() {
         // if any springs are definately broken, there is no way to do it.
         // if there are only unknown springs, there is only 1 way to do it, which is all not broken.
         let possible = if springs.contains(&b'#') { 0 } else { 1 };
@@ -189,7 +207,7 @@ fn spring_distributions(springs: &[u8], runs: &[i32], cache: &mut HashMap<Spring
         // eprintln!("  first run {firstrun} does not match {}, not possible", std::str::from_utf8(firstbatch).unwrap());
         return 0;
     }
-    for pos in 0..(firstbatch.len() + 1 - firstrun as usize) {
+    for pos in 0..(firstbatch.len() + 1 - firstrun) {
         // make sure that the spring before the run is ?, so can be good.
         if pos > 0 && firstbatch[pos-1] == b'#' {
             // it's a broken spring, so previous pos started a run, so nothing after this is possible
@@ -202,7 +220,7 @@ fn spring_distributions(springs: &[u8], runs: &[i32], cache: &mut HashMap<Spring
             if pos + firstrun + 1 < activesprings.len() {
                 let recpossible = spring_distributions(&activesprings[pos+firstrun+1..], restruns, cache, cachestat);
                 possible += recpossible;
-            } else if restruns.len() == 0 {
+            } else if restruns.is_empty() {
                 possible += 1;
             }
         }
@@ -212,7 +230,7 @@ fn spring_distributions(springs: &[u8], runs: &[i32], cache: &mut HashMap<Spring
     cache.insert(key, possible );
     cachestat.cache_miss += 1;
     // eprintln!("  mixed result, possible={possible}, stored in cache");
-    return possible;
+    possible
 }
 
 // cram as many runs as possible at the end of the springs, and return how many runs from the start do not fit,
@@ -240,7 +258,7 @@ fn min_runs_left(runs: &[i32], springs: &[u8]) -> usize {
                 }
             }
             // make sure that the item before the run is not a # known broken spring
-            if springpos as i32 - run - 1 >= 0 && springs[(springpos - run) as usize - 1] == b'#' {
+            if springpos - run > 0 && springs[(springpos - run) as usize - 1] == b'#' {
                 // it doesn't fit. Decrease springpos and try again
                 springpos -= 1;
                 continue 'springpos;
@@ -261,6 +279,7 @@ fn min_runs_left(runs: &[i32], springs: &[u8]) -> usize {
 mod tests {
     use super::*;
     use std::io::BufReader;
+    use exrunner::ExCtx;
 
     fn test_input() -> BufReader<&'static [u8]> {
         BufReader::new(
@@ -282,6 +301,14 @@ mod tests {
         assert_eq!(er.answ()[1], Some("525152".to_string()));
     }
 
+    #[test]
+    fn test_only_part2() {
+        let mut ct = ExCtx::new(solve, test_input());
+        ct.with_part(Some(2));
+        let er = ct.do_run("day 12".to_string());
+        assert_eq!(er.answ(), vec![None, Some("525152".to_string())]);
+    }
+
     fn test_slowinput() -> BufReader<&'static [u8]> {
     //    BufReader::new(".??.?.????. 2,1,2".as_bytes())
     //    BufReader::new(".?????.?.???.????? 2,1,1,1,2,1".as_bytes())
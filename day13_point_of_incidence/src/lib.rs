@@ -1,12 +1,7 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleResult};
+use grid::Grid;
 use std::collections::HashSet;
 use std::{io::BufRead, collections::HashMap};
-use std::io::Lines;
-
-struct TerrainParser<R: BufRead> {
-    input: Option<R>,
-    inputlines: Option<Lines<R>>,
-}
 
 #[derive(Debug)]
 struct Terrain {
@@ -14,94 +9,47 @@ struct Terrain {
     cols: HashMap<String, Vec<usize>>,
 }
 
-impl<R: BufRead> TerrainParser<R> {
-    fn new(input: R) -> TerrainParser<R> {
-        TerrainParser{ input: Some(input), inputlines: None }
-    }
-}
-
-impl<R: BufRead> Iterator for TerrainParser<R> {
-    type Item = Terrain;
-
-    fn next(&mut self) -> Option<Self::Item> {
+impl Terrain {
+    // the positions of each distinct row and column of the grid
+    fn new(grid: &Grid<u8>) -> Terrain {
         let mut rows = HashMap::new();
-        let mut columns = Vec::new();
-        let mut y = 0;
-        let mut lines_iter;
-        if let Some(li) = self.inputlines.take() {
-            lines_iter = li;
-        } else {
-            let input = self.input.take().unwrap();
-            lines_iter = input.lines()
-        }
-        while let Some(l) = lines_iter.next() {
-            let line = l.expect("Error reading input");
-            let trimmed = line.trim().to_owned();
-            if trimmed.len() == 0 {
-                // blank line. If nothing is read yet, try next line, otherwise return
-                if columns.len() == 0 {
-                    continue;
-                } else {
-                    break;
-                }
-            }
-            if columns.len() == 0 {
-                // first line, prepare columns
-                for _ in 0..trimmed.len() {
-                    columns.push(String::new());
-                }
-            } else if trimmed.len() != columns.len() {
-                panic!("Non-rectangular input");
-            }
-            for (x, c) in trimmed.chars().enumerate() {
-                columns[x].push(c);
-            }
-            rows.entry(trimmed).and_modify(|e: &mut Vec<usize>| e.push(y)).or_insert(vec![y]);
-            y += 1;
+        for (y, row) in grid.rows().enumerate() {
+            rows.entry(String::from_utf8_lossy(row).to_string()).or_insert_with(Vec::new).push(y);
         }
-        // put the iterator back
-        self.inputlines = Some(lines_iter);
-        if columns.len() == 0 {
-            // end of input
-            None
-        } else {
-            let mut cols = HashMap::new();
-            for (x, c) in columns.into_iter().enumerate() {
-                cols.entry(c).and_modify(|e: &mut Vec<usize>| e.push(x)).or_insert(vec![x]);
-            }
-            Some(Terrain{ rows, cols })
+        let mut cols = HashMap::new();
+        for (x, col) in grid.columns().enumerate() {
+            cols.entry(col.map(|&c| c as char).collect::<String>()).or_insert_with(Vec::new).push(x);
         }
+        Terrain { rows, cols }
     }
 }
 
-pub fn solve<R: BufRead>(input: R, er: &mut ExRunner) {
-    let terrains: TerrainParser<R> = TerrainParser::new(input);
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let terrains = Grid::parse_all(input)?;
+    er.parse_done();
     let mut notes = 0;
     let mut notes2 = 0;
-    for t in terrains {
-        let (numcols, numscols) = find_reflection(&t.cols);
-        let (numrows, numsrows) = find_reflection(&t.rows);
+    for grid in &terrains {
+        let t = Terrain::new(grid);
+        let (numcols, numscols) = find_reflection(&t.cols, er);
+        let (numrows, numsrows) = find_reflection(&t.rows, er);
         // if both are set, complain.
-        if numcols.is_some() && numrows.is_some() {
-            let nc = numcols.unwrap();
-            let nr = numrows.unwrap();
-            er.debugln(&format!("Both horizontal and vertical mirrors, at {nr} and {nc} respectively. Terrain = {:?}", t));
+        if let (Some(nc), Some(nr)) = (numcols, numrows) {
+            er.info(|| format!("Both horizontal and vertical mirrors, at {nr} and {nc} respectively. Terrain = {:?}", t));
         }
         if let Some(nc) = numcols {
-            // er.debugln(&format!("Found vertical mirror after column {nc}"));
+            er.trace(|| format!("Found vertical mirror after column {nc}"));
             notes += nc + 1;
         }
         if let Some(nr) = numrows {
-            // er.debugln(&format!("Found horizontal mirror below row {nr}"));
+            er.trace(|| format!("Found horizontal mirror below row {nr}"));
             notes += 100 * (nr + 1);
         } else if numcols.is_none() {
-            er.debugln(&format!("No mirror found! terrain = {:?}", t));
+            er.info(|| format!("No mirror found! terrain = {:?}", t));
         }
 
-        if numscols.is_some() && numsrows.is_some() {
-            let nc = numscols.unwrap();
-            let nr = numsrows.unwrap();
-            er.debugln(&format!("Both horizontal and vertical smudged mirrors, at {nr} and {nc} respectively. Terrain = {:?}", t));
+        if let (Some(nc), Some(nr)) = (numscols, numsrows) {
+            er.info(|| format!("Both horizontal and vertical smudged mirrors, at {nr} and {nc} respectively. Terrain = {:?}", t));
         }
         if let Some(nc) = numscols {
             notes2 += nc + 1;
@@ -109,14 +57,15 @@ pub fn solve<R: BufRead>(input: R, er: &mut ExRunner) {
         if let Some(nr) = numsrows {
             notes2 += 100 * (nr + 1)
         } else if numscols.is_none() {
-            er.debugln(&format!("No smudged mirror found! Terrain = {:?}", t));
+            er.info(|| format!("No smudged mirror found! Terrain = {:?}", t));
         }
     }
     er.part1(notes, Some("Sum of notes on mirrors"));
     er.part2(notes2, Some("Sum of notes on smudged mirrors"));
+    Ok(())
 }
 
-fn find_reflection(r: &HashMap<String, Vec<usize>>) -> (Option<usize>, Option<usize>) {
+fn find_reflection(r: &HashMap<String, Vec<usize>>, er: &mut ExRunner) -> (Option<usize>, Option<usize>) {
     // convert the hashmap values to a list.
     let mut poslist = Vec::new();
     // while doing that, remember any positions potentially next to a mirror, having 2 adjacent positions, or having adjacent positions with 2 rows in between.
@@ -124,7 +73,7 @@ fn find_reflection(r: &HashMap<String, Vec<usize>>) -> (Option<usize>, Option<us
     for (terrain, positions) in r {
         for &pos in positions {
             if poslist.len() <= pos {
-                let mut appendlist: Vec<(Vec<usize>, String)> = std::iter::repeat((vec![], "".to_string())).take(pos+1-poslist.len()).collect();
+                let mut appendlist: Vec<(Vec<usize>, String)> = std::iter::repeat_n((vec![], "".to_string()), pos+1-poslist.len()).collect();
                 poslist.append(&mut appendlist);
             }
             poslist[pos] = (positions.to_vec(), terrain.to_string());
@@ -177,9 +126,9 @@ fn find_reflection(r: &HashMap<String, Vec<usize>>) -> (Option<usize>, Option<us
             if let Some(prevmir) = mirror_found {
                 if (prevmir as i32 - center).abs() > (mir as i32 - center).abs() {
                     mirror_found = Some(mir);
-                    println!("Duplicate mirrors, at {prevmir} and {mir}, last one is better");
+                    er.info(|| format!("Duplicate mirrors, at {prevmir} and {mir}, last one is better"));
                 } else {
-                    println!("Duplicate mirrors, at {prevmir} and {mir}, first one is better");
+                    er.info(|| format!("Duplicate mirrors, at {prevmir} and {mir}, first one is better"));
                 }
             } else {
                 mirror_found = Some(mir);
@@ -189,33 +138,30 @@ fn find_reflection(r: &HashMap<String, Vec<usize>>) -> (Option<usize>, Option<us
             if let Some(prevmir) = smudge_mirror_found {
                 if (prevmir as i32 - center).abs() > (mir as i32 - center).abs() {
                     smudge_mirror_found = Some(mir);
-                    println!("Duplicate smudged mirrors, at {prevmir} and {mir}, last one is better");
+                    er.info(|| format!("Duplicate smudged mirrors, at {prevmir} and {mir}, last one is better"));
                 } else {
-                    println!("Duplicate smudged mirrors, at {prevmir} and {mir}, first one is better");
+                    er.info(|| format!("Duplicate smudged mirrors, at {prevmir} and {mir}, first one is better"));
                 }
             } else {
                 smudge_mirror_found = Some(mir);
-                // println!("Found a smudged mirror at {}, smudge pos is {},{}", mir, smudge_pos.unwrap().0, smudge_pos.unwrap().1);
             }
         }
     }
 
     // last resort smudge detection. First or last rows might contain a smudged mirror.
     if poslist.len() >= 2 {
-        if let Some(_smudge) = smudge_match(&poslist[0].1, &poslist[1].1) {
-            if smudge_mirror_found.is_some() {
-                println!("Duplicate smudged mirrors, at {} and 0, first one is better", smudge_mirror_found.unwrap());
+        if smudge_match(&poslist[0].1, &poslist[1].1).is_some() {
+            if let Some(prevmir) = smudge_mirror_found {
+                er.info(|| format!("Duplicate smudged mirrors, at {prevmir} and 0, first one is better"));
             } else {
                 smudge_mirror_found = Some(0);
-                // println!("Found a smudged mirror at 0, smudge pos is {_smudge},0");
             }
         }
-        if let Some(_smudge) = smudge_match(&poslist[poslist.len()-2].1, &poslist[poslist.len()-1].1) {
-            if smudge_mirror_found.is_some() && smudge_mirror_found.unwrap() != poslist.len() - 2 {
-                println!("Duplicate smudged mirrors, at {} and {}, first one is better", smudge_mirror_found.unwrap(), poslist.len() - 2);
+        if smudge_match(&poslist[poslist.len()-2].1, &poslist[poslist.len()-1].1).is_some() {
+            if let Some(prevmir) = smudge_mirror_found.filter(|&m| m != poslist.len() - 2) {
+                er.info(|| format!("Duplicate smudged mirrors, at {prevmir} and {}, first one is better", poslist.len() - 2));
             } else {
                 smudge_mirror_found = Some(poslist.len() - 2);
-                // println!("Found a smudged mirror at {}, smudge pos is {_smudge},{}", poslist.len() - 2, poslist.len() - 2);
             }
         }
     }
@@ -1,25 +1,16 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleResult};
+use grid::{Grid, Dir};
 use std::io::BufRead;
 use std::collections::HashMap;
 
-fn parse(input: impl BufRead) -> Vec<Vec<u8>> {
-    input.lines().map(|l| {
-        let line = l.expect("Error reading input");
-        line.as_bytes().to_vec()
-    }).collect()
-}
-
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let mut dish = parse(input);
-    assert!(dish.len() > 0, "Dish should not be empty");
-    assert!(dish[0].len() > 0, "Dish should not contain empty lines");
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let mut dish = Grid::parse(input)?;
     er.parse_done();
-    let dishlen = dish.len() as i32;
-    let total_load: i32 = (0..dish[0].len()).map(|x| {
+    let dishlen = dish.height() as i32;
+    let total_load: i32 = dish.columns().map(|column| {
         let mut rock_load = dishlen;
-        (0..dishlen).map(|y| {
-            let row = &dish[y as usize];
-            match row[x] {
+        column.zip(0..).map(|(&c, y)| {
+            match c {
                 b'O' => { rock_load -= 1; rock_load + 1 },
                 b'#' => { rock_load = dishlen - y - 1; 0 },
                 _ => { 0 },
@@ -34,87 +25,58 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
     let mut repeats: i64 = 0;
     let limit = 1000000000;
     while repeats < limit {
+        er.check_cancel()?;
         spin_cycle(&mut dish);
         repeats += 1;
-        dishpos.entry(dish.to_vec()).and_modify(|r| {
+        dishpos.entry(dish.clone()).and_modify(|r| {
             let cycle = repeats - *r;
             let left = limit - repeats;
             repeats += left - (left % cycle);
         }).or_insert(repeats);
     }
 
-    let actual_load: i32 = (0..dish[0].len()).map(|x| {
-        (0..dishlen).map(|y| {
-            let row = &dish[y as usize];
-            if row[x] == b'O' {
-                dishlen - y
-            } else {
-                0
-            }
-        }).sum::<i32>()
-    }).sum();
+    let actual_load: i64 = dish.iter()
+        .filter(|&(_, &c)| c == b'O')
+        .map(|(pos, _)| dish.height() as i64 - pos.y)
+        .sum();
     er.part2(actual_load, Some("Load after lots of spin cycles"));
+    Ok(())
 }
 
-fn spin_cycle(dish: &mut Vec<Vec<u8>>) {
-    do_tilt(dish, 0, -1);
-    do_tilt(dish, -1, 0);
-    do_tilt(dish, 0, 1);
-    do_tilt(dish, 1, 0);
+fn spin_cycle(dish: &mut Grid<u8>) {
+    for dir in [Dir::North, Dir::West, Dir::South, Dir::East] {
+        do_tilt(dish, dir);
+    }
 }
 
-fn do_tilt(dish: &mut Vec<Vec<u8>>, dx: i32, dy: i32) {
-    // major is the axis over which we are moving, minor is the other axis
-    let major;
-    let minor;
-    let xbase;
-    let xmult;
-    let ybase;
-    let ymult;
-    if dx != 0 {
-        major = dish[0].len() as i32;
-        minor = dish.len() as i32;
-        xbase = if dx > 0 { major - 1 } else { 0 };
-        xmult = 0;
-        ybase = 0;
-        ymult = 1;
-    } else {
-        major = dish.len() as i32;
-        minor = dish[0].len() as i32;
-        ybase = if dy > 0 { major - 1 } else { 0 };
-        ymult = 0;
-        xbase = 0;
-        xmult = 1;
-    }
-    for a1 in 0..minor {
-        // start positions for x, y
-        let mut xpos = (xbase + xmult * a1) as usize;
-        let mut ypos = (ybase + ymult * a1) as usize;
+// tilt the dish, so the round boulders roll in the given direction
+fn do_tilt(dish: &mut Grid<u8>, dir: Dir) {
+    let back = dir.reverse();
+    // walk every line from the edge the boulders roll to
+    let starts: Vec<_> = dish.edge(dir).collect();
+    for start in starts {
+        let mut pos = start;
         // start of where we pile the blocks
-        let mut xpile = xpos;
-        let mut ypile = ypos;
-        for _ in 0..major {
-            match dish[ypos][xpos] {
+        let mut pile = start;
+        while let Some(&c) = dish.get(pos) {
+            match c {
                 b'O' => {
-                    // it's a round boulder, roll it to xpile, ypile
-                    if xpos != xpile || ypos != ypile {
-                        dish[ypile][xpile] = b'O';
-                        dish[ypos][xpos] = b'.';
+                    // it's a round boulder, roll it to the pile
+                    if pos != pile {
+                        dish[pile] = b'O';
+                        dish[pos] = b'.';
                     }
                     // pile on the next position
-                    xpile = (xpile as i32 - dx) as usize;
-                    ypile = (ypile as i32 - dy) as usize;
+                    pile = pile.step(back);
                 },
                 b'#' => {
                     // it's square boulder, piling will be on the next pos
-                    xpile = (xpos as i32 - dx) as usize;
-                    ypile = (ypos as i32 - dy) as usize;
+                    pile = pos.step(back);
                 },
                 _ => { // nothing
                 }
             };
-            xpos = (xpos as i32 - dx) as usize;
-            ypos = (ypos as i32 - dy) as usize;
+            pos = pos.step(back);
         }
     }
 }
@@ -1,4 +1,4 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleError, PuzzleResult, parse_num};
 use std::{io::BufRead, collections::HashMap};
 
 pub fn hash(s: &str) -> i32 {
@@ -16,9 +16,9 @@ struct LensBox {
     lenses: HashMap<String, LensPos>,
 }
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let long = input.lines().map(|l| l.expect("Error reading input")).collect::<Vec<_>>().join("");
-    let part1: i32 = long.split(',').map(|instr| hash(instr)).sum();
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let long = input.lines().collect::<Result<Vec<_>, _>>()?.join("");
+    let part1: i32 = long.split(',').map(hash).sum();
     er.part1(part1, Some("sum of HASH value of each step"));
 
     // part2, do the lens positions. First initialize the empty boxes
@@ -28,7 +28,7 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
     }
     for instr in long.split(',') {
         if let Some((name, focalstr)) = instr.split_once('=') {
-            let focallen: i64 = focalstr.parse().expect("focal length must be numeric");
+            let focallen: i64 = parse_num(focalstr)?;
             let boxnr = hash(name);
             let lensbox = &mut boxes[boxnr as usize];
             lensbox.lenses.entry(name.to_string()).and_modify(|lp| lp.focallen = focallen)
@@ -39,22 +39,22 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
             let lensbox = &mut boxes[boxnr as usize];
             lensbox.lenses.remove(name);
         } else {
-            panic!("Unknown instruction {}", instr);
+            return Err(PuzzleError::new(format!("unknown instruction '{instr}'")));
         }
     }
 
     // now calculate the total focal strength
     let mut focalstrength = 0;
-    for boxnr in 0..boxes.len() {
-        let lensbox = &mut boxes[boxnr as usize];
+    for (boxnr, lensbox) in boxes.iter().enumerate() {
         let mut lensorder: Vec<_> = lensbox.lenses.values().collect();
         lensorder.sort_by_key(|lp| lp.boxpos);
-        // er.debugln(&format!("Boxnr {} lenses in order: {:?}", boxnr + 1, lensorder));
+        er.trace(|| format!("Boxnr {} lenses in order: {:?}", boxnr + 1, lensorder));
         focalstrength += lensorder.iter().enumerate()
             .map(|(idx, lp)| (boxnr as i64 + 1) * (idx as i64 + 1) * lp.focallen)
             .sum::<i64>();
     }
     er.part2(focalstrength, Some("Total focal strength after lens init"));
+    Ok(())
 }
 
 #[cfg(test)]
@@ -1,167 +1,78 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleResult};
+use grid::{Grid, Pos, Dir, DirSet};
 use std::{io::BufRead, collections::{HashMap, HashSet}};
 
-fn parse(input: impl BufRead) -> Vec<Vec<u8>> {
-    input.lines().map(|l| {
-        l.expect("Error reading input").as_bytes().to_vec()
-    }).collect()
-}
-
-type DIR = u8;
-const NORTH: DIR = 1;
-const WEST: DIR = 2;
-const SOUTH: DIR = 4;
-const EAST: DIR = 8;
-
-// implement / mirror
-fn mirror1(d: DIR) -> DIR {
-    match d {
-        NORTH => EAST,
-        WEST => SOUTH,
-        SOUTH => WEST,
-        EAST => NORTH,
-        _ => panic!("Unknown direction {d} in mirror /")
-    }
-}
-
-// implement \ mirror
-fn mirror2(d: DIR) -> DIR {
-    match d {
-        NORTH => WEST,
-        WEST => NORTH,
-        SOUTH => EAST,
-        EAST => SOUTH,
-        _ => panic!("Unknown direction {d} in mirror \\")
-    }
-}
-
-fn dxdy(dir: DIR) -> (i32, i32) {
-    if dir == NORTH {
-        (0, -1)
-    } else if dir == WEST {
-        (-1, 0)
-    } else if dir == SOUTH {
-        (0, 1)
-    } else if dir == EAST {
-        (1, 0)
-    } else {
-        panic!("Unknown direction {}", dir);
-    }
-}
-
-fn shine(floor: &Vec<Vec<u8>>, seen: &mut HashMap<(i32, i32), DIR>, output: &mut HashSet<(i32, DIR)>, x: i32, y: i32, dir: DIR) {
+// Follow a beam of light. Keeps the directions the beam went through each tile, and where beams leave the
+// floor, as the position and direction to shine light back in.
+fn shine(floor: &Grid<u8>, seen: &mut HashMap<Pos, DirSet>, output: &mut HashSet<(Pos, Dir)>, pos: Pos, dir: Dir) {
     // off the grid, abort.
-    if y < 0 {
-        output.insert((x, SOUTH));
+    let Some(&tile) = floor.get(pos) else {
+        output.insert((pos.step(dir.reverse()), dir.reverse()));
         return;
-    }
-    if y >= floor.len() as i32 {
-        output.insert((x, NORTH));
-        return;
-    }
-    if x < 0 {
-        output.insert((y, EAST));
-        return;
-    }
-    if x >= floor[y as usize].len() as i32 {
-        output.insert((y, WEST));
-        return;
-    }
-
-    let mut seen_dir = 0;
-    seen.entry((x, y)).and_modify(|sdir| {
-        seen_dir = *sdir;
-        *sdir |= dir;
-    }).or_insert(dir);
-    if seen_dir & dir != 0 {
+    };
+    if !seen.entry(pos).or_default().insert(dir) {
         // already seen in this direction
         return;
     }
 
-    let tile = floor[y as usize][x as usize];
-    // println!("Energize point {x},{y} direction {dir} tile {}", tile as char);
+    // println!("Energize point {pos} direction {dir} tile {}", tile as char);
     if tile == b'/' {
-        let newdir = mirror1(dir);
-        let (dx, dy) = dxdy(newdir);
-        shine(floor, seen, output, x + dx, y + dy, newdir);
-        return;
+        let newdir = dir.mirror_slash();
+        shine(floor, seen, output, pos.step(newdir), newdir);
     } else if tile == b'\\' {
-        let newdir = mirror2(dir);
-        let (dx, dy) = dxdy(newdir);
-        shine(floor, seen, output, x + dx, y + dy, newdir);
-        return;
-    } else if tile == b'-' && (dir == NORTH || dir == SOUTH) {
+        let newdir = dir.mirror_backslash();
+        shine(floor, seen, output, pos.step(newdir), newdir);
+    } else if tile == b'-' && dir.is_vertical() {
         // beam split east/west
-        shine(floor, seen, output, x + 1, y, EAST);
-        shine(floor, seen, output, x - 1, y, WEST);
-        return;
-    } else if tile == b'|' && (dir == EAST || dir == WEST) {
+        shine(floor, seen, output, pos.step(Dir::East), Dir::East);
+        shine(floor, seen, output, pos.step(Dir::West), Dir::West);
+    } else if tile == b'|' && !dir.is_vertical() {
         // beam split north/south
-        shine(floor, seen, output, x, y - 1, NORTH);
-        shine(floor, seen, output, x, y + 1, SOUTH);
-        return;
+        shine(floor, seen, output, pos.step(Dir::North), Dir::North);
+        shine(floor, seen, output, pos.step(Dir::South), Dir::South);
     } else {
         // tile == b'.' or beam splitter in wrong direction, just continue.
-        let (dx, dy) = dxdy(dir);
-        shine(floor, seen, output, x + dx, y + dy, dir);
-        return;
+        shine(floor, seen, output, pos.step(dir), dir);
     }
 }
 
-fn count_energized(floor: &Vec<Vec<u8>>, x: i32, y: i32, dir: DIR) -> (usize, HashSet<(i32, DIR)>) {
+fn count_energized(floor: &Grid<u8>, pos: Pos, dir: Dir) -> (usize, HashSet<(Pos, Dir)>) {
     let mut light_seen = HashMap::new();
     let mut output = HashSet::new();
-    shine(&floor, &mut light_seen, &mut output, x, y, dir);
+    shine(floor, &mut light_seen, &mut output, pos, dir);
     (light_seen.len(), output)
 }
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let floor = parse(input);
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let floor = Grid::parse(input)?;
     er.parse_done();
     // collect outputs already seen. We cannot improve stuff by shining light into it.
     let mut output_seen = HashSet::new();
     // determine max energized level
     let mut max_energized = None;
-    // first do all EAST and WEST light.
-    for y in 0..floor.len() {
-        for dir in [EAST, WEST] {
-            if output_seen.contains(&(y as i32, dir)) {
+    // first do all EAST and WEST light, then SOUTH and NORTH. Light going east comes in on the west edge.
+    for dir in [Dir::East, Dir::West, Dir::South, Dir::North] {
+        for pos in floor.edge(dir.reverse()) {
+            er.check_cancel()?;
+            if output_seen.contains(&(pos, dir)) {
                 // already seen as output, makes no sense shining light in here, that can only get the same
                 // or less light in
                 continue;
             }
-            let x = if dir == EAST { 0 } else { floor[y].len() - 1 };
-            let (energized, output) = count_energized(&floor, x as i32, y as i32, dir);
-            for o in output {
-                output_seen.insert(o);
-            }
-            if y == 0 && dir == EAST {
+            let (energized, output) = count_energized(&floor, pos, dir);
+            output_seen.extend(output);
+            if pos == Pos::new(0, 0) && dir == Dir::East {
                 // we solved part 1
                 er.part1(energized, Some("Number of energized tiles"));
             }
             if max_energized.is_none() || energized > max_energized.unwrap() {
                 max_energized = Some(energized);
-                // println!("New max energized level {energized} shining dir {dir} pos {y}");
-            }
-        }
-    }
-    for x in 0..floor[0].len() {
-        for dir in [SOUTH, NORTH] {
-            if output_seen.contains(&(x as i32, dir)) {
-                continue;
-            }
-            let y = if dir == SOUTH { 0 } else { floor.len() - 1};
-            let (energized, output) = count_energized(&floor, x as i32, y as i32, dir);
-            for o in output {
-                output_seen.insert(o);
-            }
-            if max_energized.is_none() || energized > max_energized.unwrap() {
-                max_energized = Some(energized);
-                // println!("New max energized level {energized} shining dir {dir} pos {x}");
+                // println!("New max energized level {energized} shining dir {dir} pos {pos}");
             }
         }
     }
     er.part2(max_energized.unwrap(), Some("Maximum energized tiles"));
+    Ok(())
 }
 
 #[cfg(test)]
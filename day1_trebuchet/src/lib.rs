@@ -1,4 +1,4 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleResult, Solver};
 use std::io::BufRead;
 
 const NUMBERS: [(&str, i32); 9] = [
@@ -13,70 +13,84 @@ const NUMBERS: [(&str, i32); 9] = [
     ("eight", 8),
 ];
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let in_vec: Vec<String> = input.lines()
-                    .map(|x| x.unwrap()).collect();
-    er.parse_done();
-    let in_digits: Vec<Vec<&str>> = in_vec.iter()
-        .map(|x| x.matches(char::is_numeric).collect()).collect();
-    let in_nums: Vec<i32> = in_digits.iter()
-        .map(|x| {
-            if x.len() > 0 {
-                format!("{}{}", x[0], x[x.len()-1]).parse().unwrap()
-            } else { 0 }
-        }
-    ).collect();
-    er.part1(in_nums.into_iter().sum::<i32>(), Some("Sum of scattered numbers"));
-    let in2_digits: Vec<i32> = in_vec.iter()
-        .map(|l| {
-            let mut lpos = l.find(|c: char| c.is_ascii_digit());
-            let mut lval = None;
-            if let Some(pos) = lpos {
-                let bval = (l.as_bytes())[pos] - b'0';
-                lval = Some(bval.into());
+pub struct Trebuchet;
+
+impl Solver for Trebuchet {
+    type Parsed = Vec<String>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+    const LABEL1: Option<&'static str> = Some("Sum of scattered numbers");
+
+    fn parse(input: impl BufRead, _er: &mut ExRunner) -> PuzzleResult<Vec<String>> {
+        Ok(input.lines().collect::<Result<_, _>>()?)
+    }
+
+    fn part1(in_vec: &mut Vec<String>, _er: &mut ExRunner) -> PuzzleResult<i32> {
+        let in_digits: Vec<Vec<&str>> = in_vec.iter()
+            .map(|x| x.matches(char::is_numeric).collect()).collect();
+        let in_nums: Vec<i32> = in_digits.iter()
+            .map(|x| {
+                if !x.is_empty() {
+                    format!("{}{}", x[0], x[x.len()-1]).parse().unwrap()
+                } else { 0 }
             }
-            for (name, val) in NUMBERS {
-                if lpos.is_some() && lpos.unwrap() <= 2 {
-                    break;
+        ).collect();
+        Ok(in_nums.into_iter().sum())
+    }
+
+    fn part2(in_vec: &mut Vec<String>, er: &mut ExRunner) -> PuzzleResult<i32> {
+        let in2_digits: Vec<i32> = in_vec.iter()
+            .map(|l| {
+                let mut lpos = l.find(|c: char| c.is_ascii_digit());
+                let mut lval = None;
+                if let Some(pos) = lpos {
+                    let bval = (l.as_bytes())[pos] - b'0';
+                    lval = Some(bval.into());
                 }
-                if let Some(pos) = l.find(name) {
-                    if lpos.is_none() || pos < lpos.unwrap() {
-                        lpos = Some(pos);
-                        lval = Some(val);
+                for (name, val) in NUMBERS {
+                    if lpos.is_some() && lpos.unwrap() <= 2 {
+                        break;
+                    }
+                    if let Some(pos) = l.find(name) {
+                        if lpos.is_none() || pos < lpos.unwrap() {
+                            lpos = Some(pos);
+                            lval = Some(val);
+                        }
                     }
                 }
-            }
-            let mut rpos = l.rfind(|c: char| c.is_ascii_digit());
-            let mut rval = None;
-            let mut mlen = 0;
-            if let Some(pos) = rpos {
-                let bval = (l.as_bytes())[pos] - b'0';
-                rval = Some(bval.into());
-                mlen = 1;
-            }
-            for (name, val) in NUMBERS {
-                if rpos.is_some() && rpos.unwrap() + mlen + name.len() > l.len() {
-                    break;
+                let mut rpos = l.rfind(|c: char| c.is_ascii_digit());
+                let mut rval = None;
+                let mut mlen = 0;
+                if let Some(pos) = rpos {
+                    let bval = (l.as_bytes())[pos] - b'0';
+                    rval = Some(bval.into());
+                    mlen = 1;
                 }
-                if let Some(pos) = l.rfind(name) {
-                    if rpos.is_none() || pos > rpos.unwrap() {
-                        rpos = Some(pos);
-                        rval = Some(val);
-                        mlen = name.len();
+                for (name, val) in NUMBERS {
+                    if rpos.is_some() && rpos.unwrap() + mlen + name.len() > l.len() {
+                        break;
+                    }
+                    if let Some(pos) = l.rfind(name) {
+                        if rpos.is_none() || pos > rpos.unwrap() {
+                            rpos = Some(pos);
+                            rval = Some(val);
+                            mlen = name.len();
+                        }
                     }
                 }
+                lval.unwrap_or(0) * 10 + rval.unwrap_or(0)
             }
-            lval.unwrap_or(0) * 10 + rval.unwrap_or(0)
-        }
-    ).collect();
-    // er.debugln(&format!("in2_digits = {:?}", in2_digits));
-    er.part2(in2_digits.into_iter().sum::<i32>(), None);
+        ).collect();
+        er.trace(|| format!("in2_digits = {:?}", in2_digits));
+        Ok(in2_digits.into_iter().sum())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::BufReader;
+    use exrunner::solve_with;
 
     fn test_input() -> BufReader<&'static [u8]> {
         BufReader::new(
@@ -103,14 +117,14 @@ zoneight234
 
     #[test]
     fn test_part1() {
-        let er = ExRunner::run("day 1 - trebuchet".to_string(), solve, test_input());
+        let er = ExRunner::run("day 1 - trebuchet".to_string(), solve_with::<Trebuchet, _>, test_input());
         er.print_raw();
         assert_eq!(er.answ()[0], Some("142".to_string()));
     }
 
     #[test]
     fn test_part2() {
-        let er = ExRunner::run("day 1 - trebuchet".to_string(), solve, test_input2());
+        let er = ExRunner::run("day 1 - trebuchet".to_string(), solve_with::<Trebuchet, _>, test_input2());
         er.print_raw();
         assert_eq!(er.answ()[1], Some("281".to_string()));
     }
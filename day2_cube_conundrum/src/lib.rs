@@ -1,56 +1,73 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleError, PuzzleResult, Solver, parse_num, numbered_lines};
 use std::io::BufRead;
-use std::collections::HashMap;
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let max_colour = HashMap::from([
-        ("red", 12),
-        ("green", 13),
-        ("blue", 14),
-    ]);
-    let mut possible = 0;
-    let mut totpower = 0;
-    for l in input.lines() {
-        let line = l.unwrap();
-        let (gamenrstr, gameplay) = line.split_once(':').expect("Input line contains no colon");
-        let mut gns = gamenrstr.split_whitespace();
-        assert_eq!(gns.next(), Some("Game"), "Invalid input line");
-        let gamenr: i32 = gns.next().expect("Input line should contain number")
-            .parse().expect("number should be numeric");
-        let subgames = gameplay.split(';');
-        let mut is_possible = true;
-        let mut min_cubes = HashMap::new();
-        for sg in subgames {
-            for cubes in sg.split(',') {
-                let numcolour = cubes.trim_start().split_once(|c: char| c.is_whitespace())
-                    .expect("gameplay must have number and colour");
-                let num: i32 = numcolour.0.parse().expect("Number of cubes should be int");
-                let max_of = max_colour.get(numcolour.1).expect("Unknown colour name");
-                if num > *max_of {
-                    is_possible = false;
+const COLOURS: [&str; 3] = ["red", "green", "blue"];
+const MAX_CUBES: [i32; 3] = [12, 13, 14];
+
+// a game, with the maximum number of cubes of each colour shown
+pub struct Game {
+    nr: i32,
+    max_cubes: [i32; 3],
+}
+
+pub struct CubeConundrum;
+
+impl Solver for CubeConundrum {
+    type Parsed = Vec<Game>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: impl BufRead, _er: &mut ExRunner) -> PuzzleResult<Vec<Game>> {
+        let mut games = Vec::new();
+        for l in numbered_lines(input) {
+            let (lnum, line) = l?;
+            let err = |msg: &str| PuzzleError::at_line(lnum, msg);
+            let (gamenrstr, gameplay) = line.split_once(':').ok_or_else(|| err("line contains no colon"))?;
+            let mut gns = gamenrstr.split_whitespace();
+            if gns.next() != Some("Game") {
+                return Err(err("line should start with \"Game\""));
+            }
+            let nr: i32 = parse_num(gns.next().ok_or_else(|| err("line should contain a game number"))?)
+                .map_err(|e| e.with_line(lnum))?;
+            let mut max_cubes = [0; 3];
+            for sg in gameplay.split(';') {
+                for cubes in sg.split(',') {
+                    let numcolour = cubes.trim_start().split_once(|c: char| c.is_whitespace())
+                        .ok_or_else(|| err("gameplay must have number and colour"))?;
+                    let num: i32 = parse_num(numcolour.0).map_err(|e| e.with_line(lnum))?;
+                    let colour = COLOURS.iter().position(|&c| c == numcolour.1)
+                        .ok_or_else(|| err(&format!("unknown colour '{}'", numcolour.1)))?;
+                    max_cubes[colour] = max_cubes[colour].max(num);
                 }
-                min_cubes.entry(numcolour.1)
-                    .and_modify(|e| if *e < num { *e = num })
-                    .or_insert(num);
             }
+            games.push(Game { nr, max_cubes });
         }
-        if is_possible {
-            possible += gamenr;
+        Ok(games)
+    }
+
+    fn part1(games: &mut Vec<Game>, _er: &mut ExRunner) -> PuzzleResult<i32> {
+        Ok(games.iter()
+            .filter(|g| g.max_cubes.iter().zip(MAX_CUBES).all(|(&n, max)| n <= max))
+            .map(|g| g.nr)
+            .sum())
+    }
+
+    fn part2(games: &mut Vec<Game>, er: &mut ExRunner) -> PuzzleResult<i32> {
+        let mut totpower = 0;
+        for g in games.iter() {
+            let power: i32 = g.max_cubes.iter().product();
+            er.trace(|| format!("Power for game {} is {}", g.nr, power));
+            totpower += power;
         }
-        let power = *min_cubes.get("red").unwrap_or(&0)
-            * *min_cubes.get("green").unwrap_or(&0)
-            * *min_cubes.get("blue").unwrap_or(&0);
-        // er.debugln(&format!("Power for game {} is {}", gamenr, power));
-        totpower += power;
+        Ok(totpower)
     }
-    er.part1(possible, None);
-    er.part2(totpower, None);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::BufReader;
+    use exrunner::solve_with;
 
     fn test_input() -> BufReader<&'static [u8]> {
         BufReader::new(
@@ -65,7 +82,7 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
 
     #[test]
     fn test_part1() {
-        let er = ExRunner::run("day 2 - cube conundrum".to_string(), solve, test_input());
+        let er = ExRunner::run("day 2 - cube conundrum".to_string(), solve_with::<CubeConundrum, _>, test_input());
         er.print_raw();
         assert_eq!(er.answ()[0], Some("8".to_string()));
         assert_eq!(er.answ()[1], Some("2286".to_string()));
@@ -1,25 +1,25 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleError, PuzzleResult, numbered_lines};
 use std::io::BufRead;
 use std::collections::HashMap;
 use std::collections::VecDeque;
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let wins: Vec<_> = input.lines().map(|l| {
-        let line = l.unwrap();
-        let (win, have) = line.split_once(':').expect("Line needs Card #:")
-            .1.split_once('|').expect("Line needs numbers | numbers");
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let wins: Vec<_> = numbered_lines(input).map(|l| {
+        let (lnum, line) = l?;
+        let (win, have) = line.split_once(':').ok_or_else(|| PuzzleError::at_line(lnum, "line needs Card #:"))?
+            .1.split_once('|').ok_or_else(|| PuzzleError::at_line(lnum, "line needs numbers | numbers"))?;
         let mut winnums = HashMap::new();
-        for w in win.trim().split_whitespace() {
+        for w in win.split_whitespace() {
             winnums.insert(w, ());
         }
-        have.trim().split_whitespace().filter(|h| winnums.contains_key(*h)).collect::<Vec<_>>().len()
-    }).collect();
+        Ok(have.split_whitespace().filter(|h| winnums.contains_key(*h)).count())
+    }).collect::<PuzzleResult<_>>()?;
     let part1: i32 = wins.iter().filter_map(|w| if *w > 0 { Some(1 << (*w-1)) } else { None }).sum();
     er.part1(part1, None);
     let mut copies_won: VecDeque<usize> = VecDeque::new();
     let part2: usize = wins.iter().map(|w| {
         let copies = copies_won.pop_front().unwrap_or(0) + 1;
-        // er.debugln(&format!("Next card has {} copies", copies));
+        er.trace(|| format!("Next card has {} copies", copies));
         // we win copies of each of the next w cards
         for i in 0..*w {
             if let Some(c) = copies_won.get_mut(i) {
@@ -32,9 +32,10 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
         copies
     }).sum();
     if !copies_won.is_empty() {
-        er.debugln(&format!("Warning, copies_won is not empty, contains: {:?}", copies_won));
+        er.info(|| format!("Warning, copies_won is not empty, contains: {:?}", copies_won));
     }
     er.part2(part2, None);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -1,5 +1,5 @@
-use exrunner::ExRunner;
-use std::{io::{BufRead, Lines}, cmp::Ordering, collections::HashMap};
+use exrunner::{ExRunner, PuzzleError, PuzzleResult, parse_num, numbered_lines};
+use std::{io::BufRead, cmp::Ordering, collections::HashMap};
 
 type PItem = i64;
 
@@ -27,21 +27,31 @@ struct ProcItemIter<'a> {
 }
 
 impl ConvMap {
-    fn new(name: &str, lines: &mut Lines<impl BufRead>) -> ConvMap {
-        let (fromname, toname) = name.split_once("-to-").expect("Map name must contain -to-");
-        let mut convmaps: Vec<_> = lines.take_while(|l| l.is_ok() && l.as_ref().unwrap() != "").map(|l| {
-            let nums: Vec<_> = l.expect("Error reading input").split_whitespace()
-                .map(|n| n.parse().expect("maps should be numeric"))
-                .collect();
-            assert_eq!(nums.len(), 3, "Maps should contain 3 numbers per line");
-            ConvMapItem{ dest_start: nums[0], src_start: nums[1], len: nums[2]}
-        }).collect();
+    // read a map with the given name, which is on line lnum, up to the next blank line
+    fn new(name: &str, lnum: usize, lines: &mut impl Iterator<Item = PuzzleResult<(usize, String)>>) -> PuzzleResult<ConvMap> {
+        let (fromname, toname) = name.split_once("-to-")
+            .ok_or_else(|| PuzzleError::at_line(lnum, "map name must contain -to-"))?;
+        let mut convmaps = Vec::new();
+        for l in lines.by_ref() {
+            let (lnum, line) = l?;
+            if line.is_empty() {
+                break;
+            }
+            let nums: Vec<PItem> = line.split_whitespace().map(parse_num).collect::<PuzzleResult<_>>()
+                .map_err(|e| e.with_line(lnum))?;
+            if nums.len() != 3 {
+                return Err(PuzzleError::at_line(lnum, "maps should contain 3 numbers per line"));
+            }
+            convmaps.push(ConvMapItem{ dest_start: nums[0], src_start: nums[1], len: nums[2]});
+        }
         convmaps.sort_by_key(|ci| ci.src_start);
         // make sure the convmaps do not overlap
-        if convmaps.len() > 0 {
+        if !convmaps.is_empty() {
             let mut last = convmaps[0].src_start + convmaps[0].len;
             for i in &convmaps[1..] {
-                assert!(last <= i.src_start, "Overlap in {name}: range {:?} starts before {last}", *i);
+                if last > i.src_start {
+                    return Err(PuzzleError::at_line(lnum, format!("overlap in {name}: range {:?} starts before {last}", *i)));
+                }
                 last = i.src_start + i.len;
             }
         }
@@ -66,7 +76,7 @@ impl ConvMap {
             }
         }
 
-        ConvMap{ fromwhat: fromname.to_string(), towhat: toname.to_string(), convmaps, revmaps, destend }
+        Ok(ConvMap{ fromwhat: fromname.to_string(), towhat: toname.to_string(), convmaps, revmaps, destend })
     }
 
     fn map(&self, inelem: PItem) -> (PItem, Option<PItem>) {
@@ -92,7 +102,7 @@ impl ConvMap {
         }
     }
 
-    fn revmap(&self, inelem: PItem) -> ProcItemIter {
+    fn revmap(&self, inelem: PItem) -> ProcItemIter<'_> {
         // try a straigt conversion. Only if the given number does not fall in any map.
         let index = self.convmaps.binary_search_by(|ci| {
             if ci.src_start > inelem {
@@ -159,63 +169,78 @@ impl<'a> Iterator for ProcItemIter<'a> {
     }
 }
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let mut l = input.lines();
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let mut l = numbered_lines(input);
     // first, get the seeds.
-    let sline= l.next().expect("Input should not be empty").expect("Error reading input");
-    let seeds_str = sline.strip_prefix("seeds:").expect("Seeds line should contains seeds:");
-    let seeds: Vec<PItem> = seeds_str.trim().split_whitespace()
-        .map(|n| n.parse().expect("Seeds should be nums")).collect();
-    assert_eq!(l.next().expect("Should have at least 1 map").expect("Error reading input"), "", "line after seeds should be blank");
+    let (_, sline) = l.next().ok_or_else(|| PuzzleError::new("input should not be empty"))??;
+    let seeds_str = sline.strip_prefix("seeds:").ok_or_else(|| PuzzleError::at_line(1, "seeds line should start with seeds:"))?;
+    let seeds: Vec<PItem> = seeds_str.split_whitespace().map(parse_num).collect::<PuzzleResult<_>>()
+        .map_err(|e| e.with_line(1))?;
+    match l.next() {
+        Some(Ok((_, blank))) if blank.is_empty() => (),
+        Some(Err(e)) => return Err(e),
+        _ => return Err(PuzzleError::at_line(2, "line after seeds should be blank")),
+    }
 
     // import the maps
     let mut maps = HashMap::new();
     while let Some(mname_line) = l.next() {
-        let mname_map = mname_line.expect("Error reading input");
-        let mname = mname_map.strip_suffix(" map:").expect("Expected a map");
-        let map = ConvMap::new(mname, &mut l);
+        let (lnum, mname_map) = mname_line?;
+        let mname = mname_map.strip_suffix(" map:").ok_or_else(|| PuzzleError::at_line(lnum, "expected a map"))?;
+        let map = ConvMap::new(mname, lnum, &mut l)?;
         maps.insert(map.fromwhat.to_string(), map);
     }
     // Now order the maps from "seed-to-X" to "X-to-location", so we can call them in order
     let mut maporder = Vec::new();
     let mut have = "seed";
     while have != "location" {
-        let cmap = maps.get(have).expect(&format!("No map available for {}", have));
+        if maporder.len() > maps.len() {
+            return Err(PuzzleError::new(format!("maps contain a cycle, at {have}")));
+        }
+        let cmap = maps.get(have).ok_or_else(|| PuzzleError::new(format!("no map available for {have}")))?;
         maporder.push(cmap);
         have = &cmap.towhat;
     }
     er.parse_done();
 
-    let mut min_loc = None;
-    for s in &seeds {
-        let mut pi = *s;
-        for &cmap in &maporder {
-            (pi, _) = cmap.map(pi);
-        }
-        // er.debugln(&format!("Got a location: {:?}", pi));
-        if min_loc.is_none() || min_loc.unwrap() > pi {
-            min_loc = Some(pi);
+    if er.wants_part(1) {
+        let mut min_loc = None;
+        for s in &seeds {
+            let mut pi = *s;
+            for &cmap in &maporder {
+                (pi, _) = cmap.map(pi);
+            }
+            er.trace(|| format!("Got a location: {:?}", pi));
+            if min_loc.is_none() || min_loc.unwrap() > pi {
+                min_loc = Some(pi);
+            }
         }
+        er.part1(min_loc.unwrap_or(0), Some("Minimum location based on individual seeds"));
+    }
+
+    if !er.wants_part(2) {
+        return Ok(());
     }
-    er.part1(min_loc.unwrap_or(0), Some("Minimum location based on individual seeds"));
 
     // part 2, treat the seeds as a (start, len) pair. Convert to list of seed ranges
     let mut seedranges = Vec::new();
     let mut si = seeds.iter();
     while let Some(s) = si.next() {
-        let lasts = *s + *si.next().expect("Expect even amount of numbers on seed line");
+        let lasts = *s + *si.next().ok_or_else(|| PuzzleError::at_line(1, "expect even amount of numbers on seed line"))?;
         seedranges.push((*s, lasts));
     }
     seedranges.sort_by_key(|&(start, _)| start);
 
     // Loop over the location rev map until we find a match
     let mut minloc: PItem = 0;
+    er.progress_start("locations searched", None);
 'location:
     loop {
+        er.progress(minloc as u64);
         let mut min_range = None;
         let piter = maporder.last().unwrap().revmap(minloc);
         for (pi, rlen) in piter {
-            // er.debugln(&format!("  revmapped to {} range {pi} len {:?}", maporder.last().unwrap().fromwhat, rlen));
+            er.trace(|| format!("  revmapped to {} range {pi} len {:?}", maporder.last().unwrap().fromwhat, rlen));
             // keep track of minimum range len
             if min_range.is_none() || (rlen.is_some() && min_range.unwrap() > rlen.unwrap()) {
                 min_range = rlen;
@@ -229,12 +254,13 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
         if let Some(mr) = min_range {
             minloc += mr;
         } else {
-            panic!("No solution found for part 2");
+            return Err(PuzzleError::new("no solution found for part 2"));
         }
     }
+    Ok(())
 }
 
-fn find_map_match(pi: PItem, mapidx: usize, rlen: Option<PItem>, maporder: &Vec<&ConvMap>, seedranges: &Vec<(PItem, PItem)>) -> Option<PItem> {
+fn find_map_match(pi: PItem, mapidx: usize, rlen: Option<PItem>, maporder: &[&ConvMap], seedranges: &[(PItem, PItem)]) -> Option<PItem> {
     let cmap = maporder[mapidx];
     // loop over entries in this map until we run out of rlen
     let mut offset = 0;
@@ -243,8 +269,10 @@ fn find_map_match(pi: PItem, mapidx: usize, rlen: Option<PItem>, maporder: &Vec<
         let piter = cmap.revmap(pi + offset);
         for (pi2, mut rlen2) in piter {
             // this range should not exceed the given range
-            if rlen.is_some() && (rlen2.is_none() || rlen2.unwrap() + offset > rlen.unwrap()) {
-                rlen2 = Some(rlen.unwrap() - offset);
+            if let Some(rl) = rlen {
+                if rlen2.is_none() || rlen2.unwrap() + offset > rl {
+                    rlen2 = Some(rl - offset);
+                }
             }
             // keep track of minimum range of current map
             if min_range.is_none() || (rlen2.is_some() && min_range.unwrap() > rlen2.unwrap()) {
@@ -270,7 +298,7 @@ fn find_map_match(pi: PItem, mapidx: usize, rlen: Option<PItem>, maporder: &Vec<
     None
 }
 
-fn find_seed_match(pi: PItem, rlen: Option<PItem>, seedranges: &Vec<(PItem, PItem)>) -> Option<PItem> {
+fn find_seed_match(pi: PItem, rlen: Option<PItem>, seedranges: &[(PItem, PItem)]) -> Option<PItem> {
     let inseed = seedranges.binary_search_by(|&(start, end)| {
         if start > pi {
             Ordering::Greater
@@ -290,16 +318,16 @@ fn find_seed_match(pi: PItem, rlen: Option<PItem>, seedranges: &Vec<(PItem, PIte
         // see if [pi, pi+rlen> overlaps with a seed range
         if pi <= seedranges[seedpos].0 && (
             rlen.is_none() || // range is infinte so seedrange is included as it starts after pi
-            pi + rlen.unwrap() - 1 >= seedranges[seedpos].0 // start of seedrange is in pi - pi+rlen-1
+            pi + rlen.unwrap() > seedranges[seedpos].0 // start of seedrange is in pi - pi+rlen-1
         ) {
             // return offset to start of the seed range
-            return Some(seedranges[seedpos].0 - pi);
+            Some(seedranges[seedpos].0 - pi)
         } else {
-            return None;
+            None
         }
     } else {
         // pi is in a seed range
-        return Some(0);
+        Some(0)
     }
 }
 
@@ -307,6 +335,7 @@ fn find_seed_match(pi: PItem, rlen: Option<PItem>, seedranges: &Vec<(PItem, PIte
 mod tests {
     use super::*;
     use std::io::BufReader;
+    use exrunner::ExCtx;
 
     fn test_input() -> BufReader<&'static [u8]> {
         BufReader::new(
@@ -353,4 +382,12 @@ humidity-to-location map:
         assert_eq!(er.answ()[0], Some("35".to_string()));
         assert_eq!(er.answ()[1], Some("46".to_string()));
     }
+
+    #[test]
+    fn test_only_part2() {
+        let mut ct = ExCtx::new(solve, test_input());
+        ct.with_part(Some(2));
+        let er = ct.do_run("day 5".to_string());
+        assert_eq!(er.answ(), vec![None, Some("46".to_string())]);
+    }
 }
@@ -1,4 +1,4 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleError, PuzzleResult, parse_num, numbered_lines};
 use std::io::BufRead;
 
 #[derive(Debug, Copy, Clone)]
@@ -7,17 +7,21 @@ struct RaceCond {
     dist: i64
 }
 
-fn parse(input: impl BufRead) -> Vec<RaceCond> {
-    let tags = vec!["Time", "Distance"];
-    let x: Vec<Vec<i64>> = input.lines().enumerate().map(|(i, l)| {
-        let inline = l.expect("Error reading input");
-        let (intag, nums) = inline.split_once(':').expect("Input should contain :");
-        assert!(i < tags.len(), "Unexpected line number {i}");
-        assert_eq!(intag, tags[i], "Expected {} got {}", tags[i], intag);
-        nums.split_whitespace().map(|n| n.parse().expect("expect numbers")).collect()
-    }).collect();
-    assert_eq!(2, x.len(), "Expected 2 input lines");
-    x[0].iter().zip(x[1].iter()).map(|(&time, &dist)| RaceCond{time, dist}).collect()
+fn parse(input: impl BufRead) -> PuzzleResult<Vec<RaceCond>> {
+    let tags = ["Time", "Distance"];
+    let x: Vec<Vec<i64>> = numbered_lines(input).map(|l| {
+        let (lnum, inline) = l?;
+        let (intag, nums) = inline.split_once(':').ok_or_else(|| PuzzleError::at_line(lnum, "line should contain :"))?;
+        let tag = tags.get(lnum - 1).ok_or_else(|| PuzzleError::at_line(lnum, "expected only 2 input lines"))?;
+        if intag != *tag {
+            return Err(PuzzleError::at_line(lnum, format!("expected {tag} got {intag}")));
+        }
+        nums.split_whitespace().map(parse_num).collect::<PuzzleResult<_>>().map_err(|e| e.with_line(lnum))
+    }).collect::<PuzzleResult<_>>()?;
+    if x.len() != 2 {
+        return Err(PuzzleError::new("expected 2 input lines"));
+    }
+    Ok(x[0].iter().zip(x[1].iter()).map(|(&time, &dist)| RaceCond{time, dist}).collect())
 }
 
 fn wins(r: RaceCond) -> Option<i64> {
@@ -27,7 +31,7 @@ fn wins(r: RaceCond) -> Option<i64> {
     // (-b +- sqrt(b**2 - 4ac)) / 2a.
     let sq = r.time * r.time - 4 * r.dist;
     if sq.is_negative() {
-        return None;
+        None
     } else {
         let sqr = (sq as f64).sqrt();
         let s1 = ((r.time as f64) - sqr) / 2.0;
@@ -38,27 +42,28 @@ fn wins(r: RaceCond) -> Option<i64> {
         if (sqr as i64).pow(2) == sq {
             // Square matches exactly. That means we do not win at the zero points,
             // but break even. So we need to exclude the endpoints, instead of include them.
-            return Some(w2 - w1 - 1);
+            Some(w2 - w1 - 1)
         } else {
-            return Some(w2 - w1 + 1);
+            Some(w2 - w1 + 1)
         }
     }
 }
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let races = parse(input);
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let races = parse(input)?;
     er.parse_done();
     let mut mult: i64 = 1;
     for r in &races {
-        let w = wins(*r).expect("Expected to win the race");
+        let w = wins(*r).ok_or_else(|| PuzzleError::new(format!("cannot win race {r:?}")))?;
         mult *= w;
     }
     er.part1(mult, None);
     let (timestr, diststr) = races.iter().
         fold(("".to_string(), "".to_string()),
         |a, r| (format!("{}{}", a.0, r.time), format!("{}{}", a.1, r.dist)));
-    let bigr = RaceCond{ time: timestr.parse().expect("num"), dist: diststr.parse().expect("num") };
-    er.part2(wins(bigr).expect("Expected to win"), None);
+    let bigr = RaceCond{ time: parse_num(&timestr)?, dist: parse_num(&diststr)? };
+    er.part2(wins(bigr).ok_or_else(|| PuzzleError::new("cannot win the big race"))?, None);
+    Ok(())
 }
 
 #[cfg(test)]
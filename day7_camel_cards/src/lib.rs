@@ -1,4 +1,4 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleError, PuzzleResult, parse_num, numbered_lines};
 use std::cmp::Ordering;
 use std::io::BufRead;
 use std::collections::HashMap;
@@ -21,7 +21,7 @@ struct Hand {
     bid: i64,
 }
 
-fn parse(input: impl BufRead) -> Vec<Hand> {
+fn parse(input: impl BufRead) -> PuzzleResult<Vec<Hand>> {
     let mut card2value = HashMap::from([
         ('A', 14 as Card),
         ('K', 13 as Card),
@@ -32,30 +32,34 @@ fn parse(input: impl BufRead) -> Vec<Hand> {
     for num in 2..10 {
         card2value.insert(char::from_digit(num, 10).unwrap(), num as Card);
     }
-    input.lines().map(|l| {
-        let inline = l.expect("Error reading input");
+    numbered_lines(input).map(|l| {
+        let (lnum, inline) = l?;
+        let err = |msg: String| PuzzleError::at_line(lnum, msg);
         let mut inwords = inline.split_whitespace();
-        let cardstr = inwords.next().expect("Need cards in input line");
-        assert_eq!(cardstr.len(), 5, "Expect 5 cards in input");
-        let bid = inwords.next().expect("need bid on input line").parse().expect("bid should be numeric");
+        let cardstr = inwords.next().ok_or_else(|| err("need cards in input line".to_string()))?;
+        if cardstr.chars().count() != 5 {
+            return Err(err(format!("expect 5 cards, got '{cardstr}'")));
+        }
+        let bid = parse_num(inwords.next().ok_or_else(|| err("need bid on input line".to_string()))?)
+            .map_err(|e| e.with_line(lnum))?;
         let mut cards = [0 as Card;5];
         let mut numvals = HashMap::new();
-        for (i, c) in cardstr.as_bytes().into_iter().enumerate() {
-            if let Some(cardval) = card2value.get(&(*c as char)) {
+        for (i, c) in cardstr.chars().enumerate() {
+            if let Some(cardval) = card2value.get(&c) {
                 cards[i] = *cardval;
                 numvals.entry(cardval).and_modify(|c| *c += 1).or_insert(1);
             } else {
-                panic!("Invalid card in input");
+                return Err(err(format!("invalid card '{c}'")));
             }
         }
         let mut maxnums: Vec<i32> = numvals.into_values().collect();
         maxnums.sort_by(|a, b| b.cmp(a));
         let cardtype = getcardtype(&maxnums);
-        Hand{ cards, cardtype, bid }
+        Ok(Hand{ cards, cardtype, bid })
     }).collect()
 }
 
-fn getcardtype(maxnums: &Vec<i32>) -> CType {
+fn getcardtype(maxnums: &[i32]) -> CType {
     if maxnums[0] == 5 {
         FIVE_KIND
     } else if maxnums[0] == 4 {
@@ -73,8 +77,8 @@ fn getcardtype(maxnums: &Vec<i32>) -> CType {
     }
 }
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let mut hands = parse(input);
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let mut hands = parse(input)?;
     er.parse_done();
     hands.sort_by(|a, b| {
         let ord = a.cardtype.cmp(&b.cardtype);
@@ -84,23 +88,23 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
             ord
         }
     });
-    // er.debugln(&format!("Sorted hands: {:?}", hands));
+    er.trace(|| format!("Sorted hands: {:?}", hands));
     er.part1(hands.iter().enumerate().map(|(i, h)| (i as i64 + 1) * h.bid).sum::<i64>(), Some("Total winnings"));
     // change hands to deal with J == Joker
     let mut hands2: Vec<_> = hands.into_iter().map(|h| {
         let mut cards = h.cards;
         let mut numvals = HashMap::new();
-        for i in 0..5 {
-            if cards[i] == 11 as Card {
-                cards[i] = 0;
+        for card in cards.iter_mut() {
+            if *card == 11 as Card {
+                *card = 0;
             }
-            numvals.entry(cards[i]).and_modify(|c| *c += 1).or_insert(1);
+            numvals.entry(*card).and_modify(|c| *c += 1).or_insert(1);
         }
         let jokers = numvals.remove(&0).unwrap_or(0);
         let mut maxnums: Vec<i32> = numvals.into_values().collect();
         maxnums.sort_by(|a, b| b.cmp(a));
         // use the jokers as the maximum
-        if maxnums.len() == 0 {
+        if maxnums.is_empty() {
             // everything is a joker!
             maxnums = vec![jokers];
         } else {
@@ -117,8 +121,9 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
             ord
         }
     });
-    // er.debugln(&format!("Sorted hands: {:?}", hands2));
+    er.trace(|| format!("Sorted hands: {:?}", hands2));
     er.part2(hands2.iter().enumerate().map(|(i, h)| (i as i64 + 1) * h.bid).sum::<i64>(), Some("Total winnings via jokers"));
+    Ok(())
 }
 
 #[cfg(test)]
@@ -144,4 +149,12 @@ QQQJA 483
         assert_eq!(er.answ()[0], Some("6440".to_string()));
         assert_eq!(er.answ()[1], Some("5905".to_string()));
     }
+
+    #[test]
+    fn test_invalid_card() {
+        let input = BufReader::new("32T3K 765\nT55X5 684\n".as_bytes());
+        let mut er = ExRunner::run("day 7".to_string(), solve, input);
+        assert_eq!(er.answ()[0], None);
+        assert_eq!(er.take_error().unwrap().to_string(), "line 2: invalid card 'X'");
+    }
 }
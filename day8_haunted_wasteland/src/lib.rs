@@ -1,4 +1,4 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleError, PuzzleResult, numbered_lines};
 use std::io::BufRead;
 use std::collections::HashMap;
 
@@ -12,28 +12,47 @@ struct Maps {
     maps: HashMap<String, Node>,
 }
 
-fn parse(input: impl BufRead) -> Maps {
-    let mut lines = input.lines();
-    let instructions = lines.next().expect("Input cannot be empty").expect("Error reading input").as_bytes().to_vec();
+fn parse(input: impl BufRead) -> PuzzleResult<Maps> {
+    let mut lines = numbered_lines(input);
+    let (_, instrline) = lines.next().ok_or_else(|| PuzzleError::new("input cannot be empty"))??;
+    let instructions = instrline.as_bytes().to_vec();
+    if let Some(pos) = instructions.iter().position(|&i| i != b'L' && i != b'R') {
+        return Err(PuzzleError::at(1, pos + 1, format!("unknown Left/Right instruction '{}'", instructions[pos] as char)));
+    }
+    if instructions.is_empty() {
+        return Err(PuzzleError::at_line(1, "no instructions"));
+    }
     let mut maps = HashMap::new();
-    while let Some(line) = lines.next() {
-        let linestr = line.expect("Error reading input");
-        if linestr.len() > 0 {
-            let (wname, nodes) = linestr.split_once('=').expect("Input needs =");
+    let mut nodelines = Vec::new();
+    for line in lines {
+        let (lnum, linestr) = line?;
+        if !linestr.is_empty() {
+            let (wname, nodes) = linestr.split_once('=').ok_or_else(|| PuzzleError::at_line(lnum, "input needs ="))?;
             let name = wname.trim().to_string();
             let brackets: &[_] = &['(', ')'];
-            let (wleft, wright) = nodes.trim().trim_matches(brackets).split_once(',').expect("Input needs ,");
+            let (wleft, wright) = nodes.trim().trim_matches(brackets).split_once(',')
+                .ok_or_else(|| PuzzleError::at_line(lnum, "input needs ,"))?;
             let left = wleft.trim().to_string();
             let right = wright.trim().to_string();
             let node = Node{ left, right };
+            nodelines.push((lnum, name.clone()));
             maps.insert(name, node);
         }
     }
-    Maps{ instructions, maps }
-} 
+    // make sure all nodes lead somewhere, so we can walk the maps without checking
+    for (lnum, name) in nodelines {
+        let node = &maps[&name];
+        for target in [&node.left, &node.right] {
+            if !maps.contains_key(target) {
+                return Err(PuzzleError::at_line(lnum, format!("undefined node '{target}'")));
+            }
+        }
+    }
+    Ok(Maps{ instructions, maps })
+}
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let navigate = parse(input);
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let navigate = parse(input)?;
     er.parse_done();
     // for the test input of part 2, there is no "AAA", so skip part1
     if navigate.maps.contains_key("AAA") {
@@ -41,13 +60,12 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
         let mut node = "AAA";
         let mut instr_index = 0;
         while node != "ZZZ" {
-            let lr = navigate.maps.get(node).expect("Undefined node");
-            if navigate.instructions[instr_index] == 'L' as u8 {
+            er.check_cancel()?;
+            let lr = &navigate.maps[node];
+            if navigate.instructions[instr_index] == b'L' {
                 node = &lr.left;
-            } else if navigate.instructions[instr_index] == 'R' as u8 {
-                node = &lr.right;
             } else {
-                panic!("Unknown Left/Right instruction");
+                node = &lr.right;
             }
             count += 1;
             instr_index += 1;
@@ -59,32 +77,40 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
     }
     // for part2, find each ??A node, and determine pathlengths to possible ??Z nodes, and a repeat count
     let startnodes = navigate.maps.keys().filter(|&n| n.ends_with("A"));
-    let allpaths: Vec<_> = startnodes.map(|s| {
-        let sol = find_pathlen(&navigate, s);
-        // er.debugln(&format!("Start at {}, paths: {:?}", s, sol));
-        sol
-    }).collect();
+    let allpaths = startnodes.map(|s| {
+        let sol = find_pathlen(&navigate, s, er)?;
+        er.trace(|| format!("Start at {}, paths: {:?}", s, sol));
+        er.count("cycles detected", sol.iter().filter(|p| p.repeat.is_some()).count() as u64);
+        Ok(sol)
+    }).collect::<PuzzleResult<Vec<_>>>()?;
+    if allpaths.is_empty() {
+        return Err(PuzzleError::new("no start nodes ending in A"));
+    }
     // we have 2 options: in the "easy" case, each node has 1 path and repeat is same as path.
     // in that case, we can just use the LCM algorithm
     let is_simple = allpaths.iter().all(|p| {
         p.len() == 1 && p[0].repeat.is_some() && p[0].repeat.unwrap() == p[0].initial
     });
     if is_simple {
-        // er.debugln("We can use the simple LCM algorithm");
+        er.trace(|| "We can use the simple LCM algorithm".to_string());
         let mut p_gcd = allpaths[0][0].initial;
-        for pathind in 1..allpaths.len() {
-            p_gcd = gcd(p_gcd, allpaths[pathind][0].initial);
+        for p in &allpaths[1..] {
+            p_gcd = gcd(p_gcd, p[0].initial);
         }
-        // er.debugln(&format!("GCD of paths is {p_gcd}"));
+        er.trace(|| format!("GCD of paths is {p_gcd}"));
         let mult = allpaths.iter().fold(p_gcd, |a, p| {
             a * p[0].initial / p_gcd
         });
         er.part2(mult, Some("Simple LCM number of steps"));
+        Ok(())
     } else {
         // loop until we find a number of repeats that makes each node end at a finish node.
         // or where each path has an initial + n * repeat that is the same number.
         let mut steps: i64 = 1;
+        er.progress_start("steps", None);
         loop {
+            er.check_cancel()?;
+            er.progress(steps as u64);
             let mut min_increment = None;
             let mut found = true;
             for nodepaths in &allpaths {
@@ -95,14 +121,12 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
                     } else {
                         let incr = if steps < p.initial {
                             Some(p.initial - steps)
-                        } else if let Some(rp) = p.repeat {
-                            Some(rp - (steps - p.initial) % rp)
-                        } else {
-                            None
-                        };
-                        if min_increment.is_none() {
-                            min_increment = incr;
-                        } else if incr.is_some() && min_increment.unwrap() > incr.unwrap() {
+                        } else { p.repeat.map(|rp| rp - (steps - p.initial) % rp) };
+                        if let (Some(mi), Some(i)) = (min_increment, incr) {
+                            if mi > i {
+                                min_increment = incr;
+                            }
+                        } else if min_increment.is_none() {
                             min_increment = incr;
                         }
                     }
@@ -113,13 +137,13 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
             }
             if found {
                 er.part2(steps, Some("Number of steps for all paths to finish"));
-                return;
+                return Ok(());
             } else if let Some(i) = min_increment {
                 steps += i;
-                er.debugln(&format!("Incrementing {i}, next try steps={steps}"));
+                er.observe("step increment", i as u64);
             } else {
                 steps += 1;
-                er.debugln(&format!("Odd, min_increment not set, just stepping 1 to {steps}"));
+                er.info(|| format!("Odd, min_increment not set, just stepping 1 to {steps}"));
             }
         }
     }
@@ -144,22 +168,22 @@ struct Repeatpos<'a> {
     instr_index: usize,
 }
 
-fn find_pathlen(nav: &Maps, start: &str) -> Vec<Pathlen> {
+fn find_pathlen(nav: &Maps, start: &str, er: &ExRunner) -> PuzzleResult<Vec<Pathlen>> {
     let mut result = Vec::new();
     let mut repeatpos = HashMap::new();
     let mut instr_index = 0;
     let mut count: i64 = 0;
     let mut node = start;
     // break out of the loop as soon as we find a repeat position
-    // XXX note: only checks for repeats at endpoints, so could loop forever on faulty input
+    // XXX note: only checks for repeats at endpoints, so could loop forever on faulty input.
+    // So it gives up when cancelled.
     loop {
-        let lr = nav.maps.get(node).expect("Undefined node");
-        if nav.instructions[instr_index] == 'L' as u8 {
+        er.check_cancel()?;
+        let lr = &nav.maps[node];
+        if nav.instructions[instr_index] == b'L' {
             node = &lr.left;
-        } else if nav.instructions[instr_index] == 'R' as u8 {
-            node = &lr.right;
         } else {
-            panic!("Unknown Left/Right instruction");
+            node = &lr.right;
         }
         count += 1;
         instr_index += 1;
@@ -173,10 +197,10 @@ fn find_pathlen(nav: &Maps, start: &str) -> Vec<Pathlen> {
                 // the rpentry points at the result array from where we repeat
                 let pl: &Pathlen = &result[*rpentry];
                 let repoffset = count - pl.initial;
-                for i in *rpentry..result.len() {
-                    result[i].repeat = Some(repoffset);
+                for r in &mut result[*rpentry..] {
+                    r.repeat = Some(repoffset);
                 }
-                return result;
+                return Ok(result);
             } else {
                 // we found an end node but we aren't repeating yet.
                 repeatpos.insert(rp, result.len());
@@ -190,6 +214,7 @@ fn find_pathlen(nav: &Maps, start: &str) -> Vec<Pathlen> {
 mod tests {
     use super::*;
     use std::io::BufReader;
+    use exrunner::{ExCtx, CancelToken};
 
     fn test_input1() -> BufReader<&'static [u8]> {
         BufReader::new(
@@ -253,4 +278,17 @@ XXX = (XXX, XXX)
         er.print_raw();
         assert_eq!(er.answ()[1], Some("6".to_string()));
     }
+
+    #[test]
+    fn test_cancel() {
+        // neither part ever gets to an end node, so only cancelling stops them
+        for input in ["L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n", "L\n\n11A = (11B, 11B)\n11B = (11A, 11A)\n"] {
+            let mut ct = ExCtx::new(solve, BufReader::new(input.as_bytes()));
+            let token = CancelToken::new();
+            ct.with_cancel(token.clone());
+            token.cancel();
+            let er = ct.do_run("day 8".to_string());
+            assert!(er.error().unwrap().is_cancelled());
+        }
+    }
 }
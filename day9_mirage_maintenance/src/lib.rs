@@ -1,10 +1,11 @@
-use exrunner::ExRunner;
+use exrunner::{ExRunner, PuzzleError, PuzzleResult, parse_num, numbered_lines};
 use std::io::BufRead;
 
-pub fn solve(input: impl BufRead, er: &mut ExRunner) {
-    let (predictprevsum, predictendsum) = input.lines().map(|l| {
-        let line = l.expect("Error reading input");
-        let nums: Vec<i32> = line.split_whitespace().map(|n| n.parse().expect("Input should be numeric")).collect();
+pub fn solve(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult {
+    let (predictprevsum, predictendsum) = numbered_lines(input).map(|l| {
+        let (lnum, line) = l?;
+        let nums: Vec<i32> = line.split_whitespace().map(parse_num).collect::<PuzzleResult<_>>()
+            .map_err(|e| e.with_line(lnum))?;
         let mut derive = vec![nums];
         while !is_all_zeros(derive.last().unwrap()) {
             let mut prev = None;
@@ -18,16 +19,17 @@ pub fn solve(input: impl BufRead, er: &mut ExRunner) {
                 }
             }).collect());
         }
-        if derive.last().unwrap().len() == 0 {
-            panic!("Cannot derive to proper sequence, input = {}", line);
+        if derive.last().unwrap().is_empty() {
+            return Err(PuzzleError::at_line(lnum, "cannot derive to proper sequence"));
         }
         let predictend: i32 = derive.iter().map(|v| v.last().unwrap()).sum();
         let predictprev: i32 = derive.iter().rev().fold(0, |a, v| v[0] - a);
-        // er.debugln(&format!("Line = {}. Derive goes {} deep. Prediction is: {} .. {}", line, derive.len(), predictprev, predictend));
-        ( predictprev, predictend )
-    }).fold((0, 0), |a, e| (a.0 + e.0, a.1 + e.1));
+        er.trace(|| format!("Line = {}. Derive goes {} deep. Prediction is: {} .. {}", line, derive.len(), predictprev, predictend));
+        Ok(( predictprev, predictend ))
+    }).try_fold((0, 0), |a, e: PuzzleResult<_>| e.map(|e| (a.0 + e.0, a.1 + e.1)))?;
     er.part1(predictendsum, Some("Sum of all predictions"));
     er.part2(predictprevsum, Some("Sum of all backwards extrapolations"));
+    Ok(())
 }
 
 trait Zero {
@@ -37,7 +39,7 @@ trait Zero {
 impl Zero for i32 { const ZERO: Self = 0; }
 impl Zero for i64 { const ZERO: Self = 0; }
 
-fn is_all_zeros<T: PartialEq + Zero>(nums: &Vec<T>) -> bool {
+fn is_all_zeros<T: PartialEq + Zero>(nums: &[T]) -> bool {
     nums.iter().all(|x| *x == T::ZERO)
 }
 
@@ -0,0 +1,163 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Global allocator that counts the allocations of every thread. Opt-in, by declaring it in the binary:
+//   #[global_allocator]
+//   static ALLOC: exrunner::CountingAlloc = exrunner::CountingAlloc;
+// The counts are per thread, so puzzles running in parallel do not see each others allocations.
+pub struct CountingAlloc;
+
+// set once the counting allocator is used, so it is known that the counts mean something
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // bytes and number of allocations, since the thread started
+    static ALLOCATED: Cell<u64> = const { Cell::new(0) };
+    static COUNT: Cell<u64> = const { Cell::new(0) };
+    // bytes allocated and not freed yet, and the highest that has been since the last reset.
+    // Can go negative, if memory from another thread is freed.
+    static LIVE: Cell<i64> = const { Cell::new(0) };
+    static PEAK: Cell<i64> = const { Cell::new(0) };
+}
+
+fn record_alloc(size: usize) {
+    if !INSTALLED.load(Ordering::Relaxed) {
+        INSTALLED.store(true, Ordering::Relaxed);
+    }
+    // try_with, because the thread locals may already be gone when a thread exits
+    let _ = ALLOCATED.try_with(|a| a.set(a.get() + size as u64));
+    let _ = COUNT.try_with(|c| c.set(c.get() + 1));
+    let _ = LIVE.try_with(|l| {
+        l.set(l.get() + size as i64);
+        let _ = PEAK.try_with(|p| p.set(p.get().max(l.get())));
+    });
+}
+
+fn record_free(size: usize) {
+    let _ = LIVE.try_with(|l| l.set(l.get() - size as i64));
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let p = System.alloc(layout);
+        if !p.is_null() {
+            record_alloc(layout.size());
+        }
+        p
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let p = System.alloc_zeroed(layout);
+        if !p.is_null() {
+            record_alloc(layout.size());
+        }
+        p
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_free(layout.size());
+    }
+
+    // a reallocation counts as a new allocation of the new size, and freeing the old one
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let p = System.realloc(ptr, layout, new_size);
+        if !p.is_null() {
+            record_free(layout.size());
+            record_alloc(new_size);
+        }
+        p
+    }
+}
+
+// What was allocated during a stage of a puzzle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AllocStats {
+    // total bytes allocated
+    pub bytes: u64,
+    // number of allocations
+    pub count: u64,
+    // highest number of bytes in use at the same time, on top of what was in use at the start
+    pub peak: u64,
+}
+
+// The allocation counters of this thread at the start of a stage
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AllocMark {
+    allocated: u64,
+    count: u64,
+    live: i64,
+}
+
+impl AllocMark {
+    // start a new stage. None if the counting allocator is not used.
+    pub(crate) fn now() -> Option<AllocMark> {
+        if !INSTALLED.load(Ordering::Relaxed) {
+            return None;
+        }
+        let live = LIVE.with(Cell::get);
+        PEAK.with(|p| p.set(live));
+        Some(AllocMark { allocated: ALLOCATED.with(Cell::get), count: COUNT.with(Cell::get), live })
+    }
+
+    // what was allocated since the start of the stage
+    pub(crate) fn stats(&self) -> AllocStats {
+        AllocStats {
+            bytes: ALLOCATED.with(Cell::get) - self.allocated,
+            count: COUNT.with(Cell::get) - self.count,
+            peak: (PEAK.with(Cell::get) - self.live).max(0) as u64,
+        }
+    }
+}
+
+// Format a number of bytes with 3 digits precision, like duration_format
+pub fn bytes_format(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut uindex = 0;
+    while value >= 1024.0 && uindex < units.len() - 1 {
+        value /= 1024.0;
+        uindex += 1;
+    }
+    if uindex == 0 {
+        format!("{bytes}B")
+    } else if value >= 100.0 {
+        format!("{value:.0}{}", units[uindex])
+    } else if value >= 10.0 {
+        format!("{value:.1}{}", units[uindex])
+    } else {
+        format!("{value:.2}{}", units[uindex])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hint::black_box;
+
+    #[global_allocator]
+    static ALLOC: CountingAlloc = CountingAlloc;
+
+    #[test]
+    fn test_counting() {
+        let _warmup = black_box(vec![0u8; 1]);
+        let mark = AllocMark::now().expect("counting allocator is installed");
+        let v = black_box(vec![0u8; 10_000]);
+        drop(v);
+        let w = black_box(vec![0u64; 100]);
+        let stats = mark.stats();
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.bytes, 10_800);
+        assert_eq!(stats.peak, 10_000);
+        drop(w);
+    }
+
+    #[test]
+    fn test_bytes_format() {
+        assert_eq!(bytes_format(999), "999B");
+        assert_eq!(bytes_format(1536), "1.50KiB");
+        assert_eq!(bytes_format(20 * 1024 * 1024), "20.0MiB");
+        assert_eq!(bytes_format(300 * 1024 * 1024 * 1024), "300GiB");
+    }
+}
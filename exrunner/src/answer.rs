@@ -0,0 +1,117 @@
+use std::fmt::{self, Display};
+
+// The answer to a part of a puzzle. Numbers are compared as numbers, and answers of more than
+// one line, like letters drawn in a grid, are kept apart from other output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i128),
+    Text(String),
+    Block(String),
+}
+
+impl Answer {
+    // text answer, or a block if it has more than one line
+    pub fn text(s: impl Into<String>) -> Answer {
+        let s = s.into();
+        match s.trim_end_matches('\n').contains('\n') {
+            true => Answer::Block(s),
+            false => Answer::Text(s),
+        }
+    }
+
+    // block answer made of lines, like the rows of a grid
+    pub fn lines<T: Display>(lines: impl IntoIterator<Item = T>) -> Answer {
+        Answer::Block(lines.into_iter().map(|l| l.to_string()).collect::<Vec<_>>().join("\n"))
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Answer::Int(_) => "int",
+            Answer::Text(_) => "text",
+            Answer::Block(_) => "block",
+        }
+    }
+
+    pub fn is_block(&self) -> bool {
+        matches!(self, Answer::Block(_))
+    }
+
+    // Does the answer match the expected answer, as written in an answers file? Whitespace around
+    // the answer, or at the end of the lines of a block, does not matter.
+    pub fn matches(&self, expected: &str) -> bool {
+        match self {
+            Answer::Int(n) => expected.trim().parse::<i128>() == Ok(*n),
+            Answer::Text(s) => s.trim() == expected.trim(),
+            Answer::Block(b) => {
+                let lines = |s: &str| s.trim_matches('\n').lines().map(str::trim_end).collect::<Vec<_>>().join("\n");
+                lines(b) == lines(expected)
+            },
+        }
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::Text(s) | Answer::Block(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+macro_rules! int_answer {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Answer {
+                Answer::Int(n as i128)
+            }
+        })*
+    };
+}
+
+int_answer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+// other simple values are compared as they are printed
+macro_rules! text_answer {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(v: $t) -> Answer {
+                Answer::Text(v.to_string())
+            }
+        })*
+    };
+}
+
+text_answer!(f32, f64, char, bool);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        Answer::text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Answer {
+        Answer::text(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(Answer::from(42u64), Answer::Int(42));
+        assert!(Answer::from(-7i32).matches(" -7"));
+        assert!(Answer::from(42usize).matches("042"));
+        assert!(!Answer::from(42usize).matches("42.0"));
+        assert_eq!(Answer::from("abc\n").kind(), "text");
+        assert!(Answer::from("abc").matches("abc "));
+        let block = Answer::lines(["#..#", "####"]);
+        assert_eq!(Answer::from("#..#\n####\n").kind(), "block");
+        assert!(block.matches("#..#  \n####\n"));
+        assert!(!block.matches("#..#"));
+        assert_eq!(block.to_string(), "#..#\n####");
+    }
+}
@@ -0,0 +1,148 @@
+use std::io::BufRead;
+use std::time::Duration;
+use crate::{ExRunner, ExCtx, PuzzleResult, duration_format};
+
+// Statistics over a number of timing samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+    pub p95: Duration,
+}
+
+impl Stats {
+    // calculate stats, or None if there are no samples
+    pub fn from_samples(samples: &[Duration]) -> Option<Stats> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        };
+        let mean_ns = sorted.iter().map(|d| d.as_nanos() as f64).sum::<f64>() / n as f64;
+        let variance = sorted.iter().map(|d| (d.as_nanos() as f64 - mean_ns).powi(2)).sum::<f64>() / n as f64;
+        // nearest rank percentile
+        let p95 = sorted[((n as f64 * 0.95).ceil() as usize).max(1) - 1];
+        Some(Stats {
+            min: sorted[0],
+            median,
+            mean: Duration::from_nanos(mean_ns.round() as u64),
+            stddev: Duration::from_nanos(variance.sqrt().round() as u64),
+            p95,
+        })
+    }
+}
+
+// Timing statistics of running an exercise a number of times
+#[derive(Debug, Clone)]
+pub struct BenchStats {
+    pub warmup: usize,
+    pub runs: usize,
+    pub parse: Option<Stats>,
+    pub part1: Option<Stats>,
+    pub part2: Option<Stats>,
+    pub total: Option<Stats>,
+}
+
+impl BenchStats {
+    pub fn stages(&self) -> [(&'static str, Option<Stats>); 4] {
+        [("parse", self.parse), ("part1", self.part1), ("part2", self.part2), ("total", self.total)]
+    }
+
+    pub fn print_raw(&self) {
+        println!("Benchmark over {} runs, after {} warm-up runs:", self.runs, self.warmup);
+        println!("{:>6} {:>8} {:>8} {:>8} {:>8} {:>8}", "", "min", "median", "mean", "stddev", "p95");
+        for (stage, stats) in self.stages() {
+            if let Some(s) = stats {
+                println!("{:>6} {:>8} {:>8} {:>8} {:>8} {:>8}", stage, duration_format(&s.min), duration_format(&s.median),
+                    duration_format(&s.mean), duration_format(&s.stddev), duration_format(&s.p95));
+            }
+        }
+    }
+}
+
+// Run an exercise warmup + runs times, on input generated by the input function. Only the timings of
+// the last "runs" runs are used. Returns the last run, and the statistics.
+// If part is given, only that part is solved.
+pub fn bench<T: BufRead>(name: String, f: fn(T, &mut ExRunner) -> PuzzleResult, mut input: impl FnMut() -> T,
+    part: Option<usize>, warmup: usize, runs: usize) -> (ExRunner<'static>, BenchStats)
+{
+    assert!(runs > 0, "Need at least 1 run to benchmark");
+    let mut run = || {
+        let mut ct = ExCtx::new(f, input());
+        ct.with_part(part);
+        ct.do_run(name.clone())
+    };
+    for _ in 0..warmup {
+        run();
+    }
+    let mut samples: [Vec<Duration>; 4] = Default::default();
+    let mut last = None;
+    for _ in 0..runs {
+        let r = run();
+        for (i, t) in [r.parsetime(), r.time1(), r.time2(), r.totaltime()].into_iter().enumerate() {
+            if let Some(t) = t {
+                samples[i].push(t);
+            }
+        }
+        last = Some(r);
+    }
+    let [parse, part1, part2, total] = samples.map(|s| Stats::from_samples(&s));
+    (last.unwrap(), BenchStats { warmup, runs, parse, part1, part2, total })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+    use std::cell::Cell;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(Stats::from_samples(&[]), None);
+        let s = Stats::from_samples(&[ms(4), ms(1), ms(3), ms(2)]).unwrap();
+        assert_eq!(s.min, ms(1));
+        assert_eq!(s.median, Duration::from_micros(2500));
+        assert_eq!(s.mean, Duration::from_micros(2500));
+        assert_eq!(s.stddev, Duration::from_nanos(1118034));
+        assert_eq!(s.p95, ms(4));
+        let samples: Vec<_> = (1..=100).rev().map(ms).collect();
+        let s = Stats::from_samples(&samples).unwrap();
+        assert_eq!(s.median, Duration::from_micros(50500));
+        assert_eq!(s.p95, ms(95));
+        let s = Stats::from_samples(&[ms(7)]).unwrap();
+        assert_eq!((s.min, s.median, s.mean, s.stddev, s.p95), (ms(7), ms(7), ms(7), ms(0), ms(7)));
+    }
+
+    fn do_lines(i: impl BufRead, r: &mut ExRunner) -> PuzzleResult {
+        let n = i.lines().count();
+        r.parse_done();
+        r.part1(n, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bench() {
+        let calls = Cell::new(0);
+        let (last, stats) = bench("bench".to_string(), do_lines, || {
+            calls.set(calls.get() + 1);
+            BufReader::new("a\nb\nc\n".as_bytes())
+        }, None, 2, 5);
+        assert_eq!(calls.get(), 7);
+        assert_eq!(last.answ(), vec![Some("3".to_string()), None]);
+        assert_eq!(stats.runs, 5);
+        assert!(stats.parse.is_some() && stats.part1.is_some() && stats.total.is_some());
+        assert!(stats.part2.is_none());
+    }
+}
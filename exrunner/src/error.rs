@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io::{self, BufRead};
+use std::str::FromStr;
+
+// Error in an exercise, usually because of invalid input. Keeps track of where in the input
+// the problem is, if known. Lines and columns count from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleError {
+    msg: String,
+    line: Option<usize>,
+    column: Option<usize>,
+    // the exercise stopped because it was asked to, not because of a problem
+    cancelled: bool,
+}
+
+pub type PuzzleResult<T = ()> = Result<T, PuzzleError>;
+
+impl PuzzleError {
+    pub fn new(msg: impl Into<String>) -> PuzzleError {
+        PuzzleError { msg: msg.into(), line: None, column: None, cancelled: false }
+    }
+
+    // the exercise gave up because it was cancelled, see ExRunner::check_cancel
+    pub fn cancelled() -> PuzzleError {
+        PuzzleError { cancelled: true, ..PuzzleError::new("cancelled") }
+    }
+
+    pub fn at_line(line: usize, msg: impl Into<String>) -> PuzzleError {
+        PuzzleError::new(msg).with_line(line)
+    }
+
+    pub fn at(line: usize, column: usize, msg: impl Into<String>) -> PuzzleError {
+        PuzzleError::new(msg).with_line(line).with_column(column)
+    }
+
+    // set the line of the error, unless it is already known
+    pub fn with_line(mut self, line: usize) -> PuzzleError {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    // set the column of the error, unless it is already known
+    pub fn with_column(mut self, column: usize) -> PuzzleError {
+        self.column.get_or_insert(column);
+        self
+    }
+
+    pub fn msg(&self) -> &str {
+        &self.msg
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+impl Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(l), Some(c)) => write!(f, "line {l}, column {c}: {}", self.msg),
+            (Some(l), None) => write!(f, "line {l}: {}", self.msg),
+            _ => write!(f, "{}", self.msg),
+        }
+    }
+}
+
+impl Error for PuzzleError {}
+
+impl From<io::Error> for PuzzleError {
+    fn from(e: io::Error) -> PuzzleError {
+        PuzzleError::new(format!("cannot read input: {e}"))
+    }
+}
+
+// parse a number, or give an error that shows what could not be parsed
+pub fn parse_num<T: FromStr>(s: &str) -> PuzzleResult<T> {
+    s.trim().parse().map_err(|_| PuzzleError::new(format!("invalid number '{s}'")))
+}
+
+// iterate over the lines of the input, numbered from 1. Read errors become puzzle errors.
+pub fn numbered_lines(input: impl BufRead) -> impl Iterator<Item = PuzzleResult<(usize, String)>> {
+    input.lines().enumerate().map(|(i, l)| l.map(|l| (i + 1, l)).map_err(|e| PuzzleError::from(e).with_line(i + 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(PuzzleError::new("no input").to_string(), "no input");
+        assert_eq!(PuzzleError::at_line(12, "invalid card 'X'").to_string(), "line 12: invalid card 'X'");
+        assert_eq!(PuzzleError::at(3, 7, "unknown tile").to_string(), "line 3, column 7: unknown tile");
+        // an existing line number is not replaced
+        assert_eq!(PuzzleError::at_line(2, "x").with_line(5).line(), Some(2));
+    }
+
+    #[test]
+    fn test_parse_num() {
+        assert_eq!(parse_num::<u32>(" 42"), Ok(42));
+        assert_eq!(parse_num::<u32>("4x").unwrap_err().msg(), "invalid number '4x'");
+        assert_eq!(parse_num::<i64>("-3").map_err(|e| e.with_line(1)), Ok(-3));
+    }
+
+    #[test]
+    fn test_numbered_lines() {
+        let lines: Vec<_> = numbered_lines(BufReader::new("a\nb\n".as_bytes())).collect();
+        assert_eq!(lines, vec![Ok((1, "a".to_string())), Ok((2, "b".to_string()))]);
+        let mut lines = numbered_lines(BufReader::new(&b"ok\n\xff\n"[..]));
+        assert!(lines.next().unwrap().is_ok());
+        assert_eq!(lines.next().unwrap().unwrap_err().line(), Some(2));
+    }
+}
@@ -1,51 +1,150 @@
 use std::time::{Duration, Instant};
 use std::io::{self, BufRead, Write};
-use std::fmt::Display;
-
-// ExRunner runs an exercise and keeps status
-pub struct ExRunner<'a, 'b> {
+use std::fmt::{self, Display};
+
+mod bench;
+pub use bench::{bench, BenchStats, Stats};
+mod error;
+pub use error::{PuzzleError, PuzzleResult, parse_num, numbered_lines};
+mod solver;
+pub use solver::{Solver, solve_with};
+mod log;
+pub use log::Level;
+mod metrics;
+pub use metrics::{Metrics, Metric, Histogram};
+mod alloc;
+pub use alloc::{CountingAlloc, AllocStats, bytes_format};
+use alloc::AllocMark;
+mod progress;
+pub use progress::{Progress, ProgressSink};
+mod cancel;
+pub use cancel::CancelToken;
+mod answer;
+pub use answer::Answer;
+use progress::ProgressState;
+
+// ExRunner runs an exercise and keeps status. It is Send, so exercises can run in parallel.
+pub struct ExRunner<'a> {
     name: String,
     start: Instant,
-    answ: [Option<Box<dyn Display + 'a>>; 2],
+    answ: [Option<Answer>; 2],
     label: [String; 2],
     parsetime: Option<Duration>,
     runtime: [Option<Duration>; 2],
     totaltime: Option<Duration>,
-    debug: Option<Box<dyn Write + 'b>>,
+    error: Option<PuzzleError>,
+    // which parts should be solved
+    parts: [bool; 2],
+    debug: Option<Box<dyn Write + Send + 'a>>,
+    // debug output above this level is not written
+    level: Level,
+    metrics: Metrics,
+    // allocations during parsing, part 1 and part 2, if the counting allocator is used
+    allocs: [Option<AllocStats>; 3],
+    // start of the current stage, for counting allocations
+    alloc_mark: Option<AllocMark>,
+    progress: Option<ProgressState<'a>>,
+    cancel: Option<CancelToken>,
+}
+
+// Verdict of comparing a given answer with the expected answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail(String),
+    Unknown,
+}
+
+impl Verdict {
+    // short machine readable status
+    pub fn status(&self) -> &'static str {
+        match self {
+            Verdict::Pass => "pass",
+            Verdict::Fail(_) => "fail",
+            Verdict::Unknown => "unknown",
+        }
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail(expected) => write!(f, "FAIL, expected {expected}"),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
 }
 
 pub struct ExCtx<'a, T: BufRead> {
-    f: fn(T, &mut ExRunner),
+    f: fn(T, &mut ExRunner) -> PuzzleResult,
     input: T,
-    debug: Option<Box<dyn Write + 'a>>,
+    part: Option<usize>,
+    debug: Option<Box<dyn Write + Send + 'a>>,
+    level: Level,
+    progress: Option<(ProgressSink<'a>, Duration)>,
+    cancel: Option<CancelToken>,
 }
 
 impl<'a, T: BufRead> ExCtx<'a, T> {
-    pub fn new(f: fn(T, &mut ExRunner), input: T) -> ExCtx<'a, T> {
-        ExCtx { f, input, debug: None }
+    pub fn new(f: fn(T, &mut ExRunner) -> PuzzleResult, input: T) -> ExCtx<'a, T> {
+        ExCtx { f, input, part: None, debug: None, level: Level::default(), progress: None, cancel: None }
+    }
+
+    // only solve the given part, 1 or 2. Solvers can check this with ExRunner::wants_part.
+    pub fn with_part(&mut self, part: Option<usize>) -> &Self {
+        self.part = part;
+        self
+    }
+
+    // only write debug output up to this level
+    pub fn with_level(&mut self, level: Level) -> &Self {
+        self.level = level;
+        self
+    }
+
+    // report the progress of the solver to the sink, at most once per interval
+    pub fn with_progress(&mut self, sink: ProgressSink<'a>, interval: Duration) -> &Self {
+        self.progress = Some((sink, interval));
+        self
+    }
+
+    // let the harness stop the exercise with the token. The exercise has to poll for it.
+    pub fn with_cancel(&mut self, token: CancelToken) -> &Self {
+        self.cancel = Some(token);
+        self
     }
 
     pub fn with_stderr(&mut self) -> &Self {
-        self.with_debugwrite(io::stderr().lock())
+        self.with_debugwrite(io::stderr())
     }
 
     pub fn with_stdout(&mut self) -> &Self {
-        self.with_debugwrite(io::stdout().lock())
+        self.with_debugwrite(io::stdout())
     }
 
-    pub fn with_debugwrite<'b>(&'b mut self, wr: impl Write + 'a) -> &'b Self
+    pub fn with_debugwrite<'b>(&'b mut self, wr: impl Write + Send + 'a) -> &'b Self
         where 'a: 'b
     {
         self.debug = Some(Box::new(wr));
         self
     }
 
-    pub fn do_run<'b>(mut self, name: String) -> ExRunner<'b, 'a>
+    pub fn do_run(mut self, name: String) -> ExRunner<'a>
     {
         let mut r = ExRunner::new(name);
         r.debug = self.debug.take();
-        (self.f)(self.input, &mut r);
+        r.level = self.level;
+        r.progress = self.progress.take().map(|(sink, interval)| ProgressState::new(sink, interval));
+        r.cancel = self.cancel.take();
+        if let Some(p) = self.part {
+            r.parts = [p == 1, p == 2];
+        }
+        r.error = (self.f)(self.input, &mut r).err();
         r.totaltime = Some(r.start.elapsed());
+        if let Some(mut p) = r.progress.take() {
+            p.done();
+        }
         if let Some(mut h) = r.debug.take() {
             h.flush().expect("Cannot flush debug");
         }
@@ -53,12 +152,12 @@ impl<'a, T: BufRead> ExCtx<'a, T> {
     }
 }
 
-impl<'a, 'b> ExRunner<'a, 'b> {
-    pub fn new(name: String) -> ExRunner<'static, 'static> {
+impl<'a> ExRunner<'a> {
+    pub fn new(name: String) -> ExRunner<'static> {
         ExRunner { name, ..Default::default() }
     }
 
-    pub fn run<T: BufRead>(name: String, f: fn(T, &mut ExRunner), input: T) -> ExRunner<'a, 'b> {
+    pub fn run<T: BufRead>(name: String, f: fn(T, &mut ExRunner) -> PuzzleResult, input: T) -> ExRunner<'a> {
         let mut ct = ExCtx::new(f, input);
         ct.with_stdout();
         ct.do_run(name)
@@ -68,32 +167,41 @@ impl<'a, 'b> ExRunner<'a, 'b> {
         &self.name
     }
 
-    fn part_x<T>(&mut self, part: usize, answ: T, label: Option<&str>)
-        where T: Display + 'a
-    {
+    // labels of the answers. Empty if no answer was given.
+    pub fn labels(&self) -> &[String; 2] {
+        &self.label
+    }
+
+    fn part_x(&mut self, part: usize, answ: Answer, label: Option<&str>) {
         let elapsed = self.start.elapsed();
+        // like the time, what was allocated is taken before storing the answer
+        let allocs = self.alloc_mark.map(|m| m.stats());
+        // answers to parts that were not asked for are ignored, but their work is not counted for the next part
+        if !self.parts[part] {
+            self.alloc_mark = AllocMark::now();
+            return;
+        }
         match self.answ[part] {
-            None => self.answ[part].insert(Box::new(answ)),
+            None => self.answ[part].insert(answ),
             Some(_) => panic!("Cannot give part{} twice", part + 1),
         };
         self.label[part] = label.unwrap_or(&format!("part{}", part + 1)).to_string();
-        let i = match self.runtime[0] {
-            None => 0,
-            _ => 1,
-        };
-        self.runtime[i] = Some(elapsed);
+        self.runtime[part] = Some(elapsed);
+        self.allocs[part + 1] = allocs;
+        self.alloc_mark = AllocMark::now();
     }
 
-    pub fn part1<T>(&mut self, answ: T, label: Option<&str>)
-        where T: Display + 'a
-    {
-        self.part_x(0, answ, label);
+    pub fn part1(&mut self, answ: impl Into<Answer>, label: Option<&str>) {
+        self.part_x(0, answ.into(), label);
     }
 
-    pub fn part2<T>(&mut self, answ: T, label: Option<&str>)
-        where T: Display + 'a
-    {
-        self.part_x(1, answ, label);
+    pub fn part2(&mut self, answ: impl Into<Answer>, label: Option<&str>) {
+        self.part_x(1, answ.into(), label);
+    }
+
+    // should the given part, 1 or 2, be solved? Solvers can use this to skip work.
+    pub fn wants_part(&self, part: usize) -> bool {
+        self.parts.get(part.wrapping_sub(1)).copied().unwrap_or(false)
     }
 
     pub fn parse_done(&mut self) {
@@ -101,35 +209,177 @@ impl<'a, 'b> ExRunner<'a, 'b> {
             None => self.parsetime.insert(self.start.elapsed()),
             Some(_) => panic!("Parsing done twice??"),
         };
+        self.allocs[0] = self.end_alloc_stage();
+    }
+
+    // allocations since the start of the current stage, and start the next one
+    fn end_alloc_stage(&mut self) -> Option<AllocStats> {
+        let stats = self.alloc_mark.map(|m| m.stats());
+        self.alloc_mark = AllocMark::now();
+        stats
+    }
+
+    // allocations during parsing, part 1 and part 2. Only counted when the CountingAlloc is used.
+    pub fn allocs(&self) -> &[Option<AllocStats>; 3] {
+        &self.allocs
+    }
+
+    // is debug output at this level written? Use this to skip work that is only needed for debugging.
+    pub fn log_enabled(&self, level: Level) -> bool {
+        self.debug.is_some() && level != Level::Off && level <= self.level
+    }
+
+    // Write a line of debug output at the given level, prefixed with the level. The message is only
+    // formatted when it is written, so disabled debug output costs next to nothing.
+    pub fn log(&mut self, level: Level, msg: impl FnOnce() -> String) {
+        if !self.log_enabled(level) {
+            return;
+        }
+        if let Some(ref mut h) = self.debug {
+            writeln!(h, "[{level}] {}", msg()).expect("Cannot write to debug");
+        }
+    }
+
+    pub fn info(&mut self, msg: impl FnOnce() -> String) {
+        self.log(Level::Info, msg);
+    }
+
+    pub fn debug(&mut self, msg: impl FnOnce() -> String) {
+        self.log(Level::Debug, msg);
     }
 
+    pub fn trace(&mut self, msg: impl FnOnce() -> String) {
+        self.log(Level::Trace, msg);
+    }
+
+    // Debug output of a message that is already formatted, written as it is, without the level.
+    // Shown at the info level, so like before there were levels, it is shown unless turned off.
     pub fn debugln(&mut self, msg: &str) {
+        if !self.log_enabled(Level::Info) {
+            return;
+        }
         if let Some(ref mut h) = self.debug {
             h.write(msg.as_bytes()).and_then(|_| h.write(b"\n")).expect("Cannot write to debug");
         }
     }
 
+    // add n to a counter, like the number of cache hits
+    pub fn count(&mut self, name: &'static str, n: u64) {
+        self.metrics.count(name, n);
+    }
+
+    // set a gauge, like the size of a cache
+    pub fn gauge(&mut self, name: &'static str, value: i64) {
+        self.metrics.gauge(name, value);
+    }
+
+    // add a value to a histogram, like the depth of a search
+    pub fn observe(&mut self, name: &'static str, value: u64) {
+        self.metrics.observe(name, value);
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    // Start a phase of the solver that can take a while, with the expected end position, or None
+    // if that is not known. Ends the previous phase. Progress is only reported if someone listens,
+    // otherwise this and the other progress calls cost next to nothing.
+    pub fn progress_start(&mut self, phase: &str, total: Option<u64>) {
+        if let Some(p) = &mut self.progress {
+            p.start(phase, total);
+        }
+    }
+
+    // set the position in the current phase
+    #[inline]
+    pub fn progress(&mut self, pos: u64) {
+        if let Some(p) = &mut self.progress {
+            p.set(pos);
+        }
+    }
+
+    // move one step further in the current phase
+    #[inline]
+    pub fn tick(&mut self) {
+        if let Some(p) = &mut self.progress {
+            p.add(1);
+        }
+    }
+
+    pub fn progress_done(&mut self) {
+        if let Some(p) = &mut self.progress {
+            p.done();
+        }
+    }
+
+    // Has the harness asked the exercise to stop? Long loops should poll this, and give up
+    // with the answers they have so far. Without a cancel token it is always false.
+    #[inline]
+    pub fn cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+
+    // give up with a cancelled error if the harness asked to stop, for use with ? in loops
+    #[inline]
+    pub fn check_cancel(&self) -> PuzzleResult {
+        match self.cancelled() {
+            true => Err(PuzzleError::cancelled()),
+            false => Ok(()),
+        }
+    }
+
+    // the error the exercise returned, if any
+    pub fn error(&self) -> Option<&PuzzleError> {
+        self.error.as_ref()
+    }
+
+    pub fn take_error(&mut self) -> Option<PuzzleError> {
+        self.error.take()
+    }
+
+    // the answers as text
     pub fn answ(&self) -> Vec<Option<String>> {
         self.answ.iter().map(|b| b.as_ref().map(|x| x.to_string())).collect()
     }
 
+    pub fn answers(&self) -> &[Option<Answer>; 2] {
+        &self.answ
+    }
+
+    // check the answers against the expected answers. Missing expected answers give Verdict::Unknown
+    pub fn verify(&self, expected: &[Option<String>]) -> Vec<Verdict> {
+        self.answ.iter().enumerate().map(|(i, answ)| {
+            match (answ, expected.get(i).and_then(|e| e.as_ref())) {
+                (_, None) => Verdict::Unknown,
+                (Some(a), Some(e)) if a.matches(e) => Verdict::Pass,
+                (_, Some(e)) => Verdict::Fail(e.to_string()),
+            }
+        }).collect()
+    }
+
     pub fn parsetime(&self) -> Option<Duration> {
         self.parsetime
     }
 
+    // end of the last stage that finished before the given time, or the start of the run
+    fn stage_start(&self, end: Duration) -> Duration {
+        [self.parsetime, self.runtime[0], self.runtime[1]].into_iter().flatten()
+            .filter(|&t| t < end)
+            .max()
+            .unwrap_or(Duration::from_secs(0))
+    }
+
     pub fn time1(&self) -> Option<Duration> {
-        self.runtime[0].map(|d| d - self.parsetime.unwrap_or(Duration::from_secs(0)))
+        self.runtime[0].map(|d| d - self.stage_start(d))
     }
 
     pub fn time2(&self) -> Option<Duration> {
-        self.runtime[1].map(|d| d - self.runtime[0].unwrap_or(Duration::from_secs(0)))
+        self.runtime[1].map(|d| d - self.stage_start(d))
     }
 
     pub fn cleanuptime(&self) -> Option<Duration> {
-        self.totaltime.map(|d| d - self.runtime[1].unwrap_or(
-                                                self.runtime[0].unwrap_or(
-                                                self.parsetime.unwrap_or(
-                                                Duration::from_secs(0)))))
+        self.totaltime.map(|d| d - self.stage_start(d))
     }
 
     pub fn totaltime(&self) -> Option<Duration> {
@@ -137,39 +387,61 @@ impl<'a, 'b> ExRunner<'a, 'b> {
     }
 
     pub fn print_raw(&self) {
-        if self.runtime[0].is_none() {
+        self.print_raw_verified(&[]);
+    }
+
+    // print raw output, marking each answer with the verdict given for it, if any
+    pub fn print_raw_verified(&self, verdicts: &[Verdict]) {
+        if self.answ.iter().all(Option::is_none) {
             println!("{} did not produce any answers", self.name);
+            for (i, v) in verdicts.iter().enumerate() {
+                if let Verdict::Fail(_) = v {
+                    println!("part{}: [{v}]", i + 1);
+                }
+            }
         } else {
             println!("{}:", self.name);
-            let answers = self.answ();
-            for i in 0..=1 {
-                if let Some(a) = &answers[i] {
-                    let sep = if a.contains("\n") { "\n" } else { " " };
-                    println!("{}:{}{}", self.label[i], sep, a);
+            for (i, answer) in self.answ.iter().enumerate() {
+                let mark = verdicts.get(i).map(|v| format!(" [{v}]")).unwrap_or_default();
+                if let Some(a) = answer {
+                    let sep = if a.is_block() { "\n" } else { " " };
+                    println!("{}:{}{}{}", self.label[i], sep, a, mark);
+                } else if let Some(Verdict::Fail(_)) = verdicts.get(i) {
+                    println!("part{}: no answer{}", i + 1, mark);
                 }
             }
+            let allocated = |i: usize| match self.allocs[i] {
+                Some(a) => format!(", allocated {} in {} allocations, peak {}", bytes_format(a.bytes), a.count, bytes_format(a.peak)),
+                None => String::new(),
+            };
             if let Some(pt) = self.parsetime {
-                println!("Parsing took: {}", duration_format(&pt))
+                println!("Parsing took: {}{}", duration_format(&pt), allocated(0))
             }
-            let ordinals = vec!["first", "second"];
-            let runtimes = vec![self.time1(), self.time2()];
+            let ordinals = ["first", "second"];
+            let runtimes = [self.time1(), self.time2()];
             for i in 0..=1 {
                 if let Some(rt) = runtimes[i] {
-                    println!("Calculating {} answer took: {}", ordinals[i], duration_format(&rt));
+                    println!("Calculating {} answer took: {}{}", ordinals[i], duration_format(&rt), allocated(i + 1));
                 }
             }
         }
+        if let Some(e) = &self.error {
+            println!("Error: {e}");
+        }
         if let Some(ct) = self.cleanuptime() {
             println!("Cleanup took: {}", duration_format(&ct));
         }
         if let Some(tt) = self.totaltime() {
             println!("Total exercise time: {}", duration_format(&tt));
         }
+        for (name, m) in self.metrics.iter() {
+            println!("{name}: {m}");
+        }
     }
 }
 
-impl<'a, 'b> Default for ExRunner<'a, 'b> {
-    fn default() -> ExRunner<'a, 'b> {
+impl<'a> Default for ExRunner<'a> {
+    fn default() -> ExRunner<'a> {
         ExRunner {
             name: "".to_string(),
             start: Instant::now(),
@@ -178,7 +450,15 @@ impl<'a, 'b> Default for ExRunner<'a, 'b> {
             parsetime: None,
             runtime: [None; 2],
             totaltime: None,
+            error: None,
+            parts: [true; 2],
             debug: None,
+            level: Level::default(),
+            metrics: Metrics::default(),
+            allocs: [None; 3],
+            alloc_mark: AllocMark::now(),
+            progress: None,
+            cancel: None,
         }
     }
 }
@@ -221,7 +501,7 @@ mod tests {
     fn create_exrunner() {
         let mut run = ExRunner{
             name: "foo".to_string(),
-            answ: [Some(Box::new(1)), None],
+            answ: [Some(Answer::Int(1)), None],
             ..Default::default()
         };
         assert_eq!(run.name, "foo".to_string());
@@ -231,7 +511,7 @@ mod tests {
     #[test]
     fn just_part1() {
         let input = BufReader::new("foo".as_bytes());
-        let run = ExRunner::run("just_part1".to_string(), |_i, r| r.part1(3, None), input);
+        let run = ExRunner::run("just_part1".to_string(), |_i, r| { r.part1(3, None); Ok(()) }, input);
         assert_eq!(run.answ(), vec![Some("3".to_string()), None]);
         assert_eq!(*run.name(), "just_part1".to_string());
         assert_eq!(run.label[0], "part1".to_string());
@@ -239,11 +519,12 @@ mod tests {
         assert!(run.time2().is_none());
     }
 
-    fn do_two_parts(i: impl BufRead, r: &mut ExRunner) {
-        let part1 = i.lines().map(|l| l.unwrap()).collect::<Vec<String>>().join(" ");
+    fn do_two_parts(i: impl BufRead, r: &mut ExRunner) -> PuzzleResult {
+        let part1 = i.lines().collect::<Result<Vec<String>, _>>()?.join(" ");
         r.part1(part1, None);
         thread::sleep(Duration::from_millis(1));
         r.part2(3.5, Some("Floating point result:"));
+        Ok(())
     }
 
     #[test]
@@ -256,10 +537,11 @@ mod tests {
         assert_eq!(run.label[1], "Floating point result:".to_string());
     }
 
-    fn do_only_part2(_i: impl BufRead, r: &mut ExRunner) {
+    fn do_only_part2(_i: impl BufRead, r: &mut ExRunner) -> PuzzleResult {
         r.parse_done();
         thread::sleep(Duration::from_millis(1));
         r.part2("static slice here", None);
+        Ok(())
     }
 
     #[test]
@@ -267,15 +549,16 @@ mod tests {
         let input = BufReader::new("nothing".as_bytes());
         let run = ExRunner::run("just_part_two".to_string(), do_only_part2, input);
         assert_eq!(run.answ(), vec![None, Some("static slice here".to_string())]);
-        assert!(run.time1() > Some(Duration::from_millis(1)));
+        assert_eq!(run.time1(), None);
         assert!(run.parsetime() < Some(Duration::from_millis(1)));
-        assert_eq!(run.time2(), None);
+        assert!(run.time2() > Some(Duration::from_millis(1)));
         assert!(run.cleanuptime() < Some(Duration::from_millis(1)));
     }
 
-    fn do_double_part1(_i: impl BufRead, r: &mut ExRunner) {
+    fn do_double_part1(_i: impl BufRead, r: &mut ExRunner) -> PuzzleResult {
         r.part1(1, None);
         r.part1(2, None); // this will panic
+        Ok(())
     }
 
     #[test]
@@ -285,6 +568,16 @@ mod tests {
         let _run = ExRunner::run("double_part1".to_string(), do_double_part1, input);
     }
 
+    #[test]
+    fn runner_is_send() {
+        fn is_send<T: Send>(_: &T) {}
+        let input = BufReader::new("foo".as_bytes());
+        let mut ct = ExCtx::new(do_two_parts, input);
+        ct.with_stderr();
+        is_send(&ct);
+        is_send(&ct.do_run("send".to_string()));
+    }
+
     #[test]
     fn test_debugln() {
         let mut debugout: Vec<u8> = Vec::new();
@@ -300,7 +593,7 @@ mod tests {
     fn test_debugrun() {
         let input = BufReader::new("".as_bytes());
         let mut debugout: Vec<u8> = Vec::new();
-        let mut ct = ExCtx::new(|_, r| r.debugln("debug via run"), input);
+        let mut ct = ExCtx::new(|_, r| { r.debugln("debug via run"); Ok(()) }, input);
         {
             ct.with_debugwrite(&mut debugout);
             let r = ct.do_run("test_debugrun".to_string());
@@ -309,6 +602,146 @@ mod tests {
         assert_eq!(debugout, b"debug via run\n");
     }
 
+    fn do_allocate(_i: impl BufRead, r: &mut ExRunner) -> PuzzleResult {
+        r.parse_done();
+        let v = std::hint::black_box(vec![1u8; 4096]);
+        r.part1(v.len(), None);
+        r.part2(0, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_allocs() {
+        // the counting allocator is installed for the tests of this crate
+        let run = ExRunner::run("allocs".to_string(), do_allocate, BufReader::new("".as_bytes()));
+        let part1 = run.allocs()[1].unwrap();
+        assert!(part1.bytes >= 4096 && part1.count >= 1, "{part1:?}");
+        assert!(part1.peak >= 4096, "{part1:?}");
+        assert_eq!(run.allocs()[2].unwrap().count, 0);
+        // what part 1 allocated is not counted for part 2 when part 1 is skipped
+        let mut ct = ExCtx::new(do_allocate, BufReader::new("".as_bytes()));
+        ct.with_part(Some(2));
+        let run = ct.do_run("allocs_part2".to_string());
+        assert!(run.allocs()[1].is_none());
+        assert_eq!(run.allocs()[2].unwrap().count, 0);
+    }
+
+    #[test]
+    fn test_progress_run() {
+        let input = BufReader::new("".as_bytes());
+        let mut phases = Vec::new();
+        let mut ct = ExCtx::new(|_, r| {
+            r.progress_start("counting", Some(3));
+            for _ in 0..3 {
+                r.tick();
+            }
+            // still open when the exercise ends
+            r.progress_start("open", None);
+            Ok(())
+        }, input);
+        {
+            ct.with_progress(Box::new(|p: &Progress| if p.done { phases.push((p.phase.clone(), p.pos)) }), Duration::ZERO);
+            ct.do_run("test_progress".to_string());
+        }
+        assert_eq!(phases, vec![("counting".to_string(), 3), ("open".to_string(), 0)]);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let token = CancelToken::new();
+        let mut ct = ExCtx::new(|_, r| {
+            r.part1(1, None);
+            r.check_cancel()?;
+            r.part2(2, None);
+            Ok(())
+        }, BufReader::new("".as_bytes()));
+        ct.with_cancel(token.clone());
+        token.cancel();
+        let run = ct.do_run("test_cancel".to_string());
+        assert!(run.error().unwrap().is_cancelled());
+        assert_eq!(run.answ(), vec![Some("1".to_string()), None]);
+        // without a token, nothing is ever cancelled
+        assert!(!ExRunner::new("test".to_string()).cancelled());
+    }
+
+    #[test]
+    fn test_levels() {
+        let input = BufReader::new("".as_bytes());
+        let mut debugout: Vec<u8> = Vec::new();
+        let mut ct = ExCtx::new(|_, r| {
+            r.info(|| "info".to_string());
+            r.debug(|| "debug".to_string());
+            r.debugln("as is");
+            // not formatted at all, when not written
+            r.trace(|| panic!("trace is not enabled"));
+            assert!(r.log_enabled(Level::Debug) && !r.log_enabled(Level::Trace));
+            Ok(())
+        }, input);
+        {
+            ct.with_debugwrite(&mut debugout);
+            ct.with_level(Level::Debug);
+            ct.do_run("test_levels".to_string());
+        }
+        assert_eq!(String::from_utf8(debugout).unwrap(), "[info] info\n[debug] debug\nas is\n");
+        // debugln is shown by default, like info
+        let mut debugout: Vec<u8> = Vec::new();
+        let mut ct = ExCtx::new(|_, r| { r.debug(|| "debug".to_string()); r.debugln("as is"); Ok(()) }, BufReader::new("".as_bytes()));
+        {
+            ct.with_debugwrite(&mut debugout);
+            ct.with_level(Level::from_verbosity(0));
+            ct.do_run("test_levels".to_string());
+        }
+        assert_eq!(debugout, b"as is\n");
+    }
+
+    #[test]
+    fn test_verify() {
+        let input = BufReader::new("foo\nbar\ntest\n".as_bytes());
+        let run = ExRunner::run("verify".to_string(), do_two_parts, input);
+        assert_eq!(run.verify(&[Some("foo bar test".to_string()), Some("3.4".to_string())]),
+            vec![Verdict::Pass, Verdict::Fail("3.4".to_string())]);
+        assert_eq!(run.verify(&[None, Some(" 3.5\n".to_string())]), vec![Verdict::Unknown, Verdict::Pass]);
+        assert_eq!(run.verify(&[]), vec![Verdict::Unknown, Verdict::Unknown]);
+        let run = ExRunner::run("verify_missing".to_string(), |_i, r| { r.part1(3, None); Ok(()) }, BufReader::new("".as_bytes()));
+        assert_eq!(run.verify(&[None, Some("3".to_string())]), vec![Verdict::Unknown, Verdict::Fail("3".to_string())]);
+    }
+
+    fn do_bad_input(i: impl BufRead, r: &mut ExRunner) -> PuzzleResult {
+        let mut sum = 0;
+        for l in numbered_lines(i) {
+            let (lnum, line) = l?;
+            sum += parse_num::<u32>(&line).map_err(|e| e.with_line(lnum))?;
+        }
+        r.part1(sum, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part() {
+        let input = BufReader::new("foo\nbar\n".as_bytes());
+        let mut ct = ExCtx::new(do_two_parts, input);
+        ct.with_part(Some(2));
+        let run = ct.do_run("part2".to_string());
+        assert_eq!(run.answ(), vec![None, Some("3.5".to_string())]);
+        assert!(run.time1().is_none() && run.time2().is_some());
+        assert!(!run.wants_part(1));
+        assert!(run.wants_part(2));
+        assert!(!run.wants_part(0) && !run.wants_part(3));
+        let run = ExRunner::run("both".to_string(), do_two_parts, BufReader::new("".as_bytes()));
+        assert!(run.wants_part(1) && run.wants_part(2));
+    }
+
+    #[test]
+    fn test_error() {
+        let run = ExRunner::run("error".to_string(), do_bad_input, BufReader::new("1\n2\n".as_bytes()));
+        assert!(run.error().is_none());
+        assert_eq!(run.answ()[0], Some("3".to_string()));
+        let mut run = ExRunner::run("error".to_string(), do_bad_input, BufReader::new("1\nX\n3\n".as_bytes()));
+        assert_eq!(run.answ()[0], None);
+        assert!(run.totaltime().is_some());
+        assert_eq!(run.take_error().unwrap().to_string(), "line 2: invalid number 'X'");
+    }
+
     #[test]
     fn test_d_format() {
         assert_eq!(duration_format(&Duration::new(0, 10)), "10ns");
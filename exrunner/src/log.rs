@@ -0,0 +1,67 @@
+use std::fmt;
+use std::str::FromStr;
+
+// How much debug output an exercise gives. Every level includes the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Level {
+    Off,
+    // things worth knowing about the input or the solution, like warnings
+    Info,
+    // what the solution is doing, in broad strokes
+    #[default]
+    Debug,
+    // details of every step, can be a lot of output
+    Trace,
+}
+
+impl Level {
+    // level for a number of -v flags on the command line, starting from info
+    pub fn from_verbosity(n: u8) -> Level {
+        match n {
+            0 => Level::Info,
+            1 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Level, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(Level::Off),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("unknown debug level '{s}', expected off, info, debug or trace")),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Level::Off => "off",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level() {
+        assert!(Level::Info < Level::Debug && Level::Debug < Level::Trace);
+        assert_eq!("TRACE".parse::<Level>(), Ok(Level::Trace));
+        assert!("loud".parse::<Level>().is_err());
+        assert_eq!(Level::from_verbosity(0), Level::Info);
+        assert_eq!(Level::from_verbosity(5), Level::Trace);
+        assert_eq!(Level::Debug.to_string(), "debug");
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+
+// Summary of the values observed for a histogram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Histogram {
+    pub count: u64,
+    pub sum: u64,
+    pub min: u64,
+    pub max: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: u64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.sum as f64 / self.count.max(1) as f64
+    }
+}
+
+// A value reported by a solver, like cache hits or the number of states explored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    // adds up everything counted
+    Counter(u64),
+    // the last value set
+    Gauge(i64),
+    // all values observed
+    Histogram(Histogram),
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Metric::Counter(n) => write!(f, "{n}"),
+            Metric::Gauge(v) => write!(f, "{v}"),
+            Metric::Histogram(h) => write!(f, "n={} min={} mean={:.1} max={}", h.count, h.min, h.mean(), h.max),
+        }
+    }
+}
+
+// The metrics of a run, by name, in the order they were first reported.
+// There are only a few per puzzle, so looking them up in a Vec is fast enough.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metrics(Vec<(&'static str, Metric)>);
+
+impl Metrics {
+    fn entry(&mut self, name: &'static str, new: Metric) -> &mut Metric {
+        let i = match self.0.iter().position(|(n, _)| *n == name) {
+            Some(i) => i,
+            None => {
+                self.0.push((name, new));
+                self.0.len() - 1
+            },
+        };
+        &mut self.0[i].1
+    }
+
+    pub fn count(&mut self, name: &'static str, n: u64) {
+        match self.entry(name, Metric::Counter(0)) {
+            Metric::Counter(c) => *c += n,
+            _ => panic!("Metric {name} is not a counter"),
+        }
+    }
+
+    pub fn gauge(&mut self, name: &'static str, value: i64) {
+        match self.entry(name, Metric::Gauge(value)) {
+            Metric::Gauge(v) => *v = value,
+            _ => panic!("Metric {name} is not a gauge"),
+        }
+    }
+
+    pub fn observe(&mut self, name: &'static str, value: u64) {
+        let new = Histogram { count: 0, sum: 0, min: value, max: value };
+        match self.entry(name, Metric::Histogram(new)) {
+            Metric::Histogram(h) => h.observe(value),
+            _ => panic!("Metric {name} is not a histogram"),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Metric> {
+        self.0.iter().find(|(n, _)| *n == name).map(|(_, m)| m)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &Metric)> {
+        self.0.iter().map(|(n, m)| (*n, m))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        let mut m = Metrics::default();
+        m.count("hits", 1);
+        m.gauge("size", 10);
+        m.count("hits", 2);
+        m.gauge("size", 7);
+        for v in [4, 1, 7] {
+            m.observe("depth", v);
+        }
+        assert_eq!(m.get("hits"), Some(&Metric::Counter(3)));
+        assert_eq!(m.get("size"), Some(&Metric::Gauge(7)));
+        assert_eq!(m.get("depth"), Some(&Metric::Histogram(Histogram { count: 3, sum: 12, min: 1, max: 7 })));
+        assert_eq!(m.get("depth").unwrap().to_string(), "n=3 min=1 mean=4.0 max=7");
+        assert_eq!(m.iter().map(|(n, _)| n).collect::<Vec<_>>(), vec!["hits", "size", "depth"]);
+        assert_eq!(m.get("misses"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Metric hits is not a gauge")]
+    fn test_wrong_kind() {
+        let mut m = Metrics::default();
+        m.count("hits", 1);
+        m.gauge("hits", 1);
+    }
+}
@@ -0,0 +1,143 @@
+use std::time::{Duration, Instant};
+
+// Reading the clock costs more than a step of a tight loop, so when updates come quickly it is
+// only read every so many updates, at most this many
+const MAX_CHECK_EVERY: u32 = 1 << 16;
+
+// Where a long running phase of a solver is at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    pub phase: String,
+    pub pos: u64,
+    // expected end position, None if the phase is open-ended
+    pub total: Option<u64>,
+    // time since the start of the phase
+    pub elapsed: Duration,
+    // true for the last report of a phase
+    pub done: bool,
+}
+
+impl Progress {
+    // estimated time left, if the total is known and there is some progress
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total?;
+        if self.pos == 0 || self.pos >= total {
+            return None;
+        }
+        Some(self.elapsed.mul_f64((total - self.pos) as f64 / self.pos as f64))
+    }
+}
+
+// Receives progress reports, at most once per interval, and once when a phase is done
+pub type ProgressSink<'a> = Box<dyn FnMut(&Progress) + Send + 'a>;
+
+// Progress reporting of a running exercise. Only exists when someone listens, so solvers
+// can report progress for free when nobody does.
+pub(crate) struct ProgressState<'a> {
+    sink: ProgressSink<'a>,
+    interval: Duration,
+    current: Option<Progress>,
+    phase_start: Instant,
+    last_report: Instant,
+    last_check: Instant,
+    // updates until the clock is read again, and how many to wait after that
+    countdown: u32,
+    check_every: u32,
+}
+
+impl<'a> ProgressState<'a> {
+    pub(crate) fn new(sink: ProgressSink<'a>, interval: Duration) -> ProgressState<'a> {
+        let now = Instant::now();
+        ProgressState { sink, interval, current: None, phase_start: now, last_report: now, last_check: now, countdown: 1, check_every: 1 }
+    }
+
+    pub(crate) fn start(&mut self, phase: &str, total: Option<u64>) {
+        self.done();
+        self.phase_start = Instant::now();
+        self.last_report = self.phase_start;
+        self.last_check = self.phase_start;
+        self.countdown = 1;
+        self.check_every = 1;
+        self.current = Some(Progress { phase: phase.to_string(), pos: 0, total, elapsed: Duration::ZERO, done: false });
+    }
+
+    pub(crate) fn set(&mut self, pos: u64) {
+        if let Some(p) = &mut self.current {
+            p.pos = pos;
+            self.countdown -= 1;
+            if self.countdown > 0 {
+                return;
+            }
+            let now = Instant::now();
+            // aim for a few clock reads per interval
+            let since_check = now - self.last_check;
+            if since_check < self.interval / 8 {
+                self.check_every = (self.check_every * 2).min(MAX_CHECK_EVERY);
+            } else if since_check > self.interval / 2 {
+                self.check_every = (self.check_every / 2).max(1);
+            }
+            self.last_check = now;
+            self.countdown = self.check_every;
+            if now - self.last_report >= self.interval {
+                self.last_report = now;
+                p.elapsed = now - self.phase_start;
+                (self.sink)(p);
+            }
+        }
+    }
+
+    pub(crate) fn add(&mut self, n: u64) {
+        let pos = self.current.as_ref().map(|p| p.pos + n).unwrap_or(0);
+        self.set(pos);
+    }
+
+    // report the end of the current phase, if any
+    pub(crate) fn done(&mut self) {
+        if let Some(mut p) = self.current.take() {
+            p.elapsed = self.phase_start.elapsed();
+            p.done = true;
+            (self.sink)(&p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_progress() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let reports = seen.clone();
+        let mut state = ProgressState::new(Box::new(move |p: &Progress| reports.lock().unwrap().push(p.clone())), Duration::ZERO);
+        state.set(5);
+        state.start("search", Some(10));
+        state.add(2);
+        state.add(3);
+        state.start("open", None);
+        state.done();
+        state.done();
+        let seen = seen.lock().unwrap();
+        let summary: Vec<_> = seen.iter().map(|p| (p.phase.as_str(), p.pos, p.done)).collect();
+        assert_eq!(summary, vec![("search", 2, false), ("search", 5, false), ("search", 5, true), ("open", 0, true)]);
+        assert!(seen[1].eta().is_some());
+        assert_eq!(seen[3].eta(), None);
+    }
+
+    #[test]
+    fn test_throttle() {
+        let count = Arc::new(Mutex::new(0));
+        let c = count.clone();
+        let mut state = ProgressState::new(Box::new(move |_: &Progress| *c.lock().unwrap() += 1), Duration::from_secs(3600));
+        state.start("busy", None);
+        for i in 0..1000 {
+            state.set(i);
+        }
+        state.done();
+        // only the end of the phase is reported
+        assert_eq!(*count.lock().unwrap(), 1);
+        // and the clock is not read for every update
+        assert!(state.check_every > 1);
+    }
+}
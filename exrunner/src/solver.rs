@@ -0,0 +1,96 @@
+use std::io::BufRead;
+use crate::{Answer, ExRunner, PuzzleResult};
+
+// A puzzle solution split up in separate stages: parse the input, then solve part 1 and part 2.
+// The stages are run by solve_with, which takes care of the timings, so they are always measured the same way.
+// The ExRunner is passed in for debug output only; the answers are the return values.
+pub trait Solver {
+    type Parsed;
+    type Answer1: Into<Answer>;
+    type Answer2: Into<Answer>;
+
+    // labels to show with the answers, instead of "part1" and "part2"
+    const LABEL1: Option<&'static str> = None;
+    const LABEL2: Option<&'static str> = None;
+
+    fn parse(input: impl BufRead, er: &mut ExRunner) -> PuzzleResult<Self::Parsed>;
+
+    // the parsed input is mutable, so part 1 can leave results behind for part 2
+    fn part1(parsed: &mut Self::Parsed, er: &mut ExRunner) -> PuzzleResult<Self::Answer1>;
+
+    fn part2(parsed: &mut Self::Parsed, er: &mut ExRunner) -> PuzzleResult<Self::Answer2>;
+}
+
+// Run a Solver, timing each stage. As a plain function, so it can be used anywhere an exercise function is expected.
+// Parts that are not wanted are skipped.
+pub fn solve_with<S: Solver, T: BufRead>(input: T, er: &mut ExRunner) -> PuzzleResult {
+    let mut parsed = S::parse(input, er)?;
+    er.parse_done();
+    if er.wants_part(1) {
+        let answ = S::part1(&mut parsed, er)?;
+        er.part1(answ, S::LABEL1);
+    }
+    if er.wants_part(2) {
+        let answ = S::part2(&mut parsed, er)?;
+        er.part2(answ, S::LABEL2);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+    use std::thread;
+    use std::time::Duration;
+    use crate::{ExCtx, PuzzleError, parse_num};
+
+    struct SumProduct;
+
+    impl Solver for SumProduct {
+        type Parsed = Vec<u64>;
+        type Answer1 = u64;
+        type Answer2 = u64;
+        const LABEL1: Option<&'static str> = Some("Sum");
+
+        fn parse(input: impl BufRead, _er: &mut ExRunner) -> PuzzleResult<Vec<u64>> {
+            let nums = input.lines().map(|l| parse_num(&l?)).collect::<PuzzleResult<Vec<_>>>()?;
+            thread::sleep(Duration::from_millis(2));
+            Ok(nums)
+        }
+
+        fn part1(nums: &mut Vec<u64>, er: &mut ExRunner) -> PuzzleResult<u64> {
+            er.debugln("adding");
+            Ok(nums.iter().sum())
+        }
+
+        fn part2(nums: &mut Vec<u64>, _er: &mut ExRunner) -> PuzzleResult<u64> {
+            if nums.is_empty() {
+                return Err(PuzzleError::new("no numbers"));
+            }
+            Ok(nums.iter().product())
+        }
+    }
+
+    #[test]
+    fn test_solve_with() {
+        let input = BufReader::new("2\n3\n4\n".as_bytes());
+        let run = ExRunner::run("solver".to_string(), solve_with::<SumProduct, _>, input);
+        assert_eq!(run.answ(), vec![Some("9".to_string()), Some("24".to_string())]);
+        assert_eq!(run.labels(), &["Sum".to_string(), "part2".to_string()]);
+        // parsing time is not counted for part 1
+        assert!(run.parsetime() > Some(Duration::from_millis(2)));
+        assert!(run.time1() < Some(Duration::from_millis(2)));
+        assert!(run.error().is_none());
+
+        let mut ct = ExCtx::new(solve_with::<SumProduct, _>, BufReader::new("".as_bytes()));
+        ct.with_part(Some(1));
+        let run = ct.do_run("solver".to_string());
+        assert_eq!(run.answ(), vec![Some("0".to_string()), None]);
+        assert!(run.error().is_none());
+
+        let run = ExRunner::run("solver".to_string(), solve_with::<SumProduct, _>, BufReader::new("".as_bytes()));
+        assert_eq!(run.answ(), vec![Some("0".to_string()), None]);
+        assert_eq!(run.error().map(|e| e.msg()), Some("no numbers"));
+    }
+}
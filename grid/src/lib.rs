@@ -0,0 +1,261 @@
+use std::fmt;
+use std::io::BufRead;
+use std::ops::{Index, IndexMut};
+use exrunner::{PuzzleError, PuzzleResult, numbered_lines};
+
+mod pos;
+pub use pos::{Pos, Dir, DirSet};
+
+// A rectangular grid of cells, like a map in the puzzle input. Stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    // grid with every cell set to the same value
+    pub fn new(width: usize, height: usize, value: T) -> Grid<T>
+        where T: Clone
+    {
+        Grid { width, height, cells: vec![value; width * height] }
+    }
+
+    // Grid from its rows, which must all be as long. The first row is on line first_line of the input,
+    // to point at the right line if they are not.
+    pub fn from_rows(rows: Vec<Vec<T>>, first_line: usize) -> PuzzleResult<Grid<T>> {
+        let width = rows.first().map(Vec::len).ok_or_else(|| PuzzleError::at_line(first_line, "grid should not be empty"))?;
+        if width == 0 {
+            return Err(PuzzleError::at_line(first_line, "grid should not have empty lines"));
+        }
+        if let Some(y) = rows.iter().position(|r| r.len() != width) {
+            return Err(PuzzleError::at_line(first_line + y, format!("grid should be rectangular, line should be {width} long")));
+        }
+        let height = rows.len();
+        Ok(Grid { width, height, cells: rows.into_iter().flatten().collect() })
+    }
+
+    // Parse a grid, converting each character to a cell. Characters that give None are an error.
+    pub fn parse_with(input: impl BufRead, cell: impl Fn(u8) -> Option<T>) -> PuzzleResult<Grid<T>> {
+        let mut grids = Grid::parse_all_with(input, cell)?;
+        match grids.len() {
+            1 => Ok(grids.remove(0)),
+            0 => Err(PuzzleError::new("grid should not be empty")),
+            _ => Err(PuzzleError::new("grid should not have empty lines")),
+        }
+    }
+
+    // Parse grids that are separated by empty lines
+    pub fn parse_all_with(input: impl BufRead, cell: impl Fn(u8) -> Option<T>) -> PuzzleResult<Vec<Grid<T>>> {
+        let mut grids = Vec::new();
+        let mut rows = Vec::new();
+        let mut first_line = 1;
+        for l in numbered_lines(input) {
+            let (lnum, line) = l?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                if !rows.is_empty() {
+                    grids.push(Grid::from_rows(std::mem::take(&mut rows), first_line)?);
+                }
+                continue;
+            }
+            if rows.is_empty() {
+                first_line = lnum;
+            }
+            let row = line.bytes().enumerate()
+                .map(|(x, c)| cell(c).ok_or_else(|| PuzzleError::at(lnum, x + 1, format!("invalid character '{}'", c as char))))
+                .collect::<PuzzleResult<Vec<_>>>()?;
+            rows.push(row);
+        }
+        if !rows.is_empty() {
+            grids.push(Grid::from_rows(rows, first_line)?);
+        }
+        Ok(grids)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        (0..self.width as i64).contains(&pos.x) && (0..self.height as i64).contains(&pos.y)
+    }
+
+    fn offset(&self, pos: Pos) -> Option<usize> {
+        self.contains(pos).then(|| pos.y as usize * self.width + pos.x as usize)
+    }
+
+    // the cell at pos, or None if that is off the grid
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.offset(pos).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.offset(pos).map(|i| &mut self.cells[i])
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    // all positions, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| Pos::new((i % width) as i64, (i / width) as i64))
+    }
+
+    // all cells with their position, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    // the positions on the edge of the grid on the given side, from west to east or north to south
+    pub fn edge(&self, side: Dir) -> impl Iterator<Item = Pos> {
+        let (w, h) = (self.width as i64, self.height as i64);
+        let (len, start, step) = match side {
+            Dir::North => (w, Pos::new(0, 0), Dir::East),
+            Dir::South => (w, Pos::new(0, h - 1), Dir::East),
+            Dir::West => (h, Pos::new(0, 0), Dir::South),
+            Dir::East => (h, Pos::new(w - 1, 0), Dir::South),
+        };
+        (0..len).scan(start, move |p, _| {
+            let cur = *p;
+            *p = p.step(step);
+            Some(cur)
+        })
+    }
+
+    // the 4 neighbours of pos that are on the grid
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        pos.neighbours4().filter(|&p| self.contains(p))
+    }
+
+    // the 8 neighbours of pos that are on the grid, including the diagonal ones
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        pos.neighbours8().filter(|&p| self.contains(p))
+    }
+
+    // grid with the rows as columns
+    pub fn transpose(&self) -> Grid<T>
+        where T: Clone
+    {
+        let cells = self.columns().flatten().cloned().collect();
+        Grid { width: self.height, height: self.width, cells }
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+}
+
+impl Grid<u8> {
+    // parse a grid of characters
+    pub fn parse(input: impl BufRead) -> PuzzleResult<Grid<u8>> {
+        Grid::parse_with(input, Some)
+    }
+
+    // parse grids of characters that are separated by empty lines
+    pub fn parse_all(input: impl BufRead) -> PuzzleResult<Vec<Grid<u8>>> {
+        Grid::parse_all_with(input, Some)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos).unwrap_or_else(|| panic!("position {pos} is off the grid"))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos).unwrap_or_else(|| panic!("position {pos} is off the grid"))
+    }
+}
+
+// show a grid of characters the way it was in the input
+impl fmt::Display for Grid<u8> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<_> = self.rows().map(String::from_utf8_lossy).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    fn grid() -> Grid<u8> {
+        Grid::parse(BufReader::new("abc\ndef\n".as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let g = grid();
+        assert_eq!((g.width(), g.height()), (3, 2));
+        assert_eq!(g[Pos::new(1, 1)], b'e');
+        assert_eq!(g.get(Pos::new(3, 0)), None);
+        assert_eq!(g.to_string(), "abc\ndef");
+        let e = Grid::parse(BufReader::new("abc\nde\n".as_bytes())).unwrap_err();
+        assert_eq!(e.line(), Some(2));
+        let e = Grid::parse_with(BufReader::new("..\n.x\n".as_bytes()), |c| (c == b'.').then_some(0)).unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 2: invalid character 'x'");
+        assert!(Grid::parse(BufReader::new("".as_bytes())).is_err());
+    }
+
+    #[test]
+    fn test_parse_all() {
+        let grids = Grid::parse_all(BufReader::new("ab\ncd\n\n\nxyz\n".as_bytes())).unwrap();
+        assert_eq!(grids.len(), 2);
+        assert_eq!(grids[1].row(0), b"xyz");
+        let e = Grid::parse_all(BufReader::new("ab\n\nxyz\nx\n".as_bytes())).unwrap_err();
+        assert_eq!(e.line(), Some(4));
+    }
+
+    #[test]
+    fn test_views() {
+        let mut g = grid();
+        assert_eq!(g.rows().collect::<Vec<_>>(), vec![b"abc", b"def"]);
+        assert_eq!(g.column(1).copied().collect::<Vec<_>>(), b"be");
+        let t = g.transpose();
+        assert_eq!(t.to_string(), "ad\nbe\ncf");
+        assert_eq!(t.columns().map(|c| c.copied().collect::<Vec<_>>()).collect::<Vec<_>>(), vec![b"abc", b"def"]);
+        g[Pos::new(0, 1)] = b'x';
+        g.row_mut(0)[2] = b'y';
+        assert_eq!(g.to_string(), "aby\nxef");
+        assert_eq!(g.iter().find(|(_, &c)| c == b'x').map(|(p, _)| p), Some(Pos::new(0, 1)));
+        assert_eq!(g.map(|&c| c == b'x').get(Pos::new(0, 1)), Some(&true));
+    }
+
+    #[test]
+    fn test_edges_and_neighbours() {
+        let g = grid();
+        assert_eq!(g.edge(Dir::East).collect::<Vec<_>>(), vec![Pos::new(2, 0), Pos::new(2, 1)]);
+        assert_eq!(g.edge(Dir::South).count(), 3);
+        assert_eq!(g.neighbours4(Pos::new(0, 0)).collect::<Vec<_>>(), vec![Pos::new(1, 0), Pos::new(0, 1)]);
+        assert_eq!(g.neighbours8(Pos::new(1, 0)).count(), 5);
+    }
+}
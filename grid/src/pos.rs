@@ -0,0 +1,229 @@
+use std::fmt;
+use std::ops::{Add, BitOr, Sub};
+
+// Position in a grid, x to the east and y to the south. Signed, so a step off the grid is still a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Pos {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Pos {
+    pub const fn new(x: i64, y: i64) -> Pos {
+        Pos { x, y }
+    }
+
+    pub fn step(self, dir: Dir) -> Pos {
+        self + dir.delta()
+    }
+
+    // the 4 positions next to this one, in the order of Dir::ALL
+    pub fn neighbours4(self) -> impl Iterator<Item = Pos> {
+        Dir::ALL.into_iter().map(move |d| self.step(d))
+    }
+
+    // the 8 positions around this one, clockwise starting north
+    pub fn neighbours8(self) -> impl Iterator<Item = Pos> {
+        Dir::ALL.into_iter().flat_map(move |d| [self.step(d), self.step(d).step(d.turn_right())])
+    }
+
+    pub fn manhattan(self, other: Pos) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+impl Add for Pos {
+    type Output = Pos;
+
+    fn add(self, other: Pos) -> Pos {
+        Pos::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Pos {
+    type Output = Pos;
+
+    fn sub(self, other: Pos) -> Pos {
+        Pos::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+// Direction in a grid, with north up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir {
+    // clockwise, starting north
+    pub const ALL: [Dir; 4] = [Dir::North, Dir::East, Dir::South, Dir::West];
+
+    // the step to take in this direction
+    pub const fn delta(self) -> Pos {
+        match self {
+            Dir::North => Pos::new(0, -1),
+            Dir::East => Pos::new(1, 0),
+            Dir::South => Pos::new(0, 1),
+            Dir::West => Pos::new(-1, 0),
+        }
+    }
+
+    pub const fn turn_right(self) -> Dir {
+        match self {
+            Dir::North => Dir::East,
+            Dir::East => Dir::South,
+            Dir::South => Dir::West,
+            Dir::West => Dir::North,
+        }
+    }
+
+    pub const fn turn_left(self) -> Dir {
+        self.reverse().turn_right()
+    }
+
+    pub const fn reverse(self) -> Dir {
+        self.turn_right().turn_right()
+    }
+
+    // where a beam goes when it hits a / mirror
+    pub const fn mirror_slash(self) -> Dir {
+        match self {
+            Dir::North => Dir::East,
+            Dir::East => Dir::North,
+            Dir::South => Dir::West,
+            Dir::West => Dir::South,
+        }
+    }
+
+    // where a beam goes when it hits a \ mirror
+    pub const fn mirror_backslash(self) -> Dir {
+        self.mirror_slash().reverse()
+    }
+
+    pub const fn is_vertical(self) -> bool {
+        matches!(self, Dir::North | Dir::South)
+    }
+}
+
+impl fmt::Display for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Dir::North => "north",
+            Dir::East => "east",
+            Dir::South => "south",
+            Dir::West => "west",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// A set of directions, like the ends of a pipe, stored as bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DirSet(u8);
+
+impl DirSet {
+    pub const EMPTY: DirSet = DirSet(0);
+
+    const fn bit(dir: Dir) -> u8 {
+        1 << dir as u8
+    }
+
+    pub const fn with(self, dir: Dir) -> DirSet {
+        DirSet(self.0 | DirSet::bit(dir))
+    }
+
+    pub const fn without(self, dir: Dir) -> DirSet {
+        DirSet(self.0 & !DirSet::bit(dir))
+    }
+
+    pub const fn contains(self, dir: Dir) -> bool {
+        self.0 & DirSet::bit(dir) != 0
+    }
+
+    // add a direction, returns false if it was already in the set
+    pub fn insert(&mut self, dir: Dir) -> bool {
+        let new = !self.contains(dir);
+        *self = self.with(dir);
+        new
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Dir> {
+        Dir::ALL.into_iter().filter(move |&d| self.contains(d))
+    }
+}
+
+impl From<Dir> for DirSet {
+    fn from(dir: Dir) -> DirSet {
+        DirSet::EMPTY.with(dir)
+    }
+}
+
+impl FromIterator<Dir> for DirSet {
+    fn from_iter<I: IntoIterator<Item = Dir>>(iter: I) -> DirSet {
+        iter.into_iter().fold(DirSet::EMPTY, DirSet::with)
+    }
+}
+
+impl BitOr<Dir> for Dir {
+    type Output = DirSet;
+
+    fn bitor(self, other: Dir) -> DirSet {
+        DirSet::from(self).with(other)
+    }
+}
+
+impl BitOr<Dir> for DirSet {
+    type Output = DirSet;
+
+    fn bitor(self, other: Dir) -> DirSet {
+        self.with(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir() {
+        assert_eq!(Dir::North.turn_right(), Dir::East);
+        assert_eq!(Dir::North.turn_left(), Dir::West);
+        assert_eq!(Dir::East.reverse(), Dir::West);
+        assert_eq!(Dir::ALL.map(Dir::mirror_slash), [Dir::East, Dir::North, Dir::West, Dir::South]);
+        assert_eq!(Dir::ALL.map(Dir::mirror_backslash), [Dir::West, Dir::South, Dir::East, Dir::North]);
+        assert_eq!(Pos::new(2, 2).step(Dir::North), Pos::new(2, 1));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let p = Pos::new(0, 0);
+        assert_eq!(p.neighbours4().collect::<Vec<_>>(), vec![Pos::new(0, -1), Pos::new(1, 0), Pos::new(0, 1), Pos::new(-1, 0)]);
+        let n8: Vec<_> = p.neighbours8().collect();
+        assert_eq!(n8.len(), 8);
+        assert_eq!(n8[1], Pos::new(1, -1));
+        assert!(n8.iter().all(|n| n.manhattan(p) <= 2 && *n != p));
+    }
+
+    #[test]
+    fn test_dirset() {
+        let mut s = Dir::North | Dir::South;
+        assert!(s.contains(Dir::South) && !s.contains(Dir::East));
+        assert!(s.insert(Dir::East));
+        assert!(!s.insert(Dir::East));
+        assert_eq!(s.without(Dir::North).iter().collect::<Vec<_>>(), vec![Dir::East, Dir::South]);
+        assert_eq!(Dir::ALL.into_iter().collect::<DirSet>(), Dir::North | Dir::East | Dir::South | Dir::West);
+    }
+}